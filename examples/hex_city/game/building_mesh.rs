@@ -0,0 +1,375 @@
+use pixel_rs::plugins::core::render_graph::MAIN_PASS;
+use pixel_rs::app::PluginInfo;
+use pixel_rs::plugins::core::render_plugin::RenderPlugin;
+use pixel_rs::plugins::core::camera_plugin::CameraPlugin;
+use pixel_rs::plugins::core::buffer_vec::BufferVec;
+use pixel_rs::plugins::renderer_plugins::{
+    multi_instance_mesh_renderer::MultiInstanceMeshRenderer, sprite_renderer::SpritePlugin,
+    tilemap_renderer::TileMapRenderer,
+};
+use std::ops::Range;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::{Buffer, PipelineLayout, RenderPipeline, ShaderModule, TextureFormat};
+
+use pixel_rs::{
+    app::Plugin,
+    ecs::{singletons::Singletons, world::World},
+    math::color::Color,
+    plugins::{
+        asset_types::shader::Shader,
+        core::{
+            asset_storage::{AssetRef, AssetStorage},
+            camera_plugin::CameraBindGroup,
+            render_plugin::{Gpu, Renderer},
+            shader_reload::{ShaderChanged, ShaderReloadPlugin, ShaderWatcher},
+        },
+    },
+    storage::Storage,
+};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct BuildingMeshVertex {
+    pub position: [f32; 3],
+}
+
+impl BuildingMeshVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 1] = wgpu::vertex_attr_array![0 => Float32x3];
+
+    pub fn decs<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<BuildingMeshVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+struct BuildingRendererData {
+    render_pipeline: wgpu::RenderPipeline,
+    // Kept around to rebuild the pipeline when the shader changes
+    render_pipeline_layout: PipelineLayout,
+    shader: AssetRef<Shader>,
+    building_data_bind_group: wgpu::BindGroup,
+    building_data_bind_group_layout: wgpu::BindGroupLayout,
+
+    vertices: BufferVec<BuildingMeshVertex>,
+    indices: BufferVec<u32>,
+    instance_data: BufferVec<BuildingInstanceData>,
+    instance_index: Vec<BuildingInstanceIndex>,
+}
+
+pub struct BuildingMesh {
+    pub vertices: Vec<BuildingMeshVertex>,
+    pub indices: Vec<u32>,
+    pub color: Color,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable, Default)]
+pub struct BuildingInstanceData {
+    pub color: [f32; 4],
+}
+
+pub struct BuildingInstanceIndex {
+    instance_data_index: u32,
+    indices_range: Range<u32>,
+}
+
+pub struct BuildingRenderPlugin;
+
+const SHADER_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/hex_city/game/building_shader.wgsl"
+);
+
+impl Plugin for BuildingRenderPlugin {
+    // Renderers draw in build order, buildings go on top of the ground and sprites
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<RenderPlugin>(),
+            PluginInfo::of::<CameraPlugin>(),
+            PluginInfo::of::<TileMapRenderer>(),
+            PluginInfo::of::<SpritePlugin>(),
+            PluginInfo::of::<MultiInstanceMeshRenderer>(),
+            PluginInfo::of::<ShaderReloadPlugin>(),
+        ]
+    }
+
+    fn build(app: &mut pixel_rs::app::App) {
+        let (gpu, camera_data, assets, shader_watcher) = app
+            .storage
+            .singletons
+            .get_many_mut::<(Gpu, CameraBindGroup, AssetStorage, ShaderWatcher)>()
+            .unwrap();
+
+        let shader = shader_watcher.load(assets, SHADER_PATH, include_str!("building_shader.wgsl"));
+
+        let instance_data = BufferVec::new(
+            &gpu.device,
+            wgpu::BufferUsages::STORAGE,
+            "Building data buffer",
+            64,
+        );
+
+        let building_data_bind_group_layout =
+            gpu.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("building_data_bind_group_layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    }],
+                });
+
+        let building_data_bind_group = create_building_data_bind_group(
+            &gpu.device,
+            &building_data_bind_group_layout,
+            instance_data.buffer(),
+        );
+
+        let render_pipeline_layout =
+            gpu.device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Render Pipeline Layout"),
+                    bind_group_layouts: &[&camera_data.layout, &building_data_bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+        let render_pipeline = assets
+            .get_data(&shader)
+            .create_pipeline(&gpu.device, SHADER_PATH, |module| {
+                create_render_pipeline(
+                    &gpu.device,
+                    &render_pipeline_layout,
+                    gpu.surface_config.format,
+                    module,
+                )
+            })
+            .unwrap();
+
+        let vertices = BufferVec::new(
+            &gpu.device,
+            wgpu::BufferUsages::VERTEX,
+            "Vertex Buffer",
+            1024,
+        );
+
+        let indices = BufferVec::new(
+            &gpu.device,
+            wgpu::BufferUsages::INDEX,
+            "Building index buffer",
+            1024 * 3,
+        );
+
+        let building_renderer_data = BuildingRendererData {
+            render_pipeline,
+            render_pipeline_layout,
+            shader,
+            building_data_bind_group,
+            building_data_bind_group_layout,
+            vertices,
+            indices,
+            instance_data,
+            instance_index: Vec::new(),
+        };
+
+        app.storage.singletons.insert(building_renderer_data);
+        app.storage.add_listener::<ShaderChanged>(on_shader_changed);
+        app.render_graph.add_to_pass(MAIN_PASS, Box::new(BuildingRenderPlugin));
+
+        app.schedular
+            .add_system(pixel_rs::app::SystemStage::PreRender, prepare_renderer_data);
+    }
+}
+
+fn create_building_data_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    buffer: &Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Building data bind group"),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: buffer.as_entire_binding(),
+        }],
+    })
+}
+
+fn prepare_renderer_data(storage: &mut Storage) {
+    // Placeholder until buildings come from the level
+    let list = [BuildingMesh {
+        vertices: vec![
+            BuildingMeshVertex {
+                position: [0.5, 0.5, 1.0],
+            },
+            BuildingMeshVertex {
+                position: [-0.5, 0.5, 1.0],
+            },
+            BuildingMeshVertex {
+                position: [-0.5, -0.5, 1.0],
+            },
+            BuildingMeshVertex {
+                position: [0.5, -0.5, 1.0],
+            },
+        ],
+        indices: vec![0, 1, 2, 0, 2, 3],
+        color: Color::new(0.3, 0.4, 0.6, 1.0),
+    }];
+
+    let (gpu, render_data) = storage
+        .singletons
+        .get_many_mut::<(Gpu, BuildingRendererData)>()
+        .unwrap();
+
+    render_data.vertices.clear();
+    render_data.indices.clear();
+    render_data.instance_data.clear();
+    render_data.instance_index.clear();
+
+    for building in list.iter() {
+        let offset = render_data.vertices.len() as u32;
+        let indices_len = render_data.indices.len();
+        let index_range = indices_len as u32..(indices_len + building.indices.len()) as u32;
+
+        render_data.vertices.extend_from_slice(&building.vertices);
+
+        for index in building.indices.iter() {
+            render_data.indices.push(*index + offset);
+        }
+
+        let building_instance_data = BuildingInstanceData {
+            color: building.color.into(),
+        };
+
+        render_data.instance_data.push(building_instance_data);
+
+        let color_index = render_data.instance_data.len() - 1;
+
+        let index_data = BuildingInstanceIndex {
+            indices_range: index_range,
+            instance_data_index: color_index as u32,
+        };
+
+        render_data.instance_index.push(index_data);
+
+    }
+
+    render_data.vertices.write_buffer(&gpu.device, &gpu.queue);
+    render_data.indices.write_buffer(&gpu.device, &gpu.queue);
+
+    if render_data.instance_data.write_buffer(&gpu.device, &gpu.queue) {
+        render_data.building_data_bind_group = create_building_data_bind_group(
+            &gpu.device,
+            &render_data.building_data_bind_group_layout,
+            render_data.instance_data.buffer(),
+        );
+    }
+}
+
+impl Renderer for BuildingRenderPlugin {
+    fn render<'pass, 'encoder: 'pass, 'world: 'encoder>(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'encoder>,
+        _world: &'world World,
+        singletons: &'world Singletons,
+    ) {
+        let camera_data = singletons.get::<CameraBindGroup>().unwrap();
+
+        let data = singletons.get::<BuildingRendererData>().unwrap();
+
+        render_pass.set_pipeline(&data.render_pipeline);
+        render_pass.set_vertex_buffer(0, data.vertices.buffer().slice(..));
+        render_pass.set_index_buffer(data.indices.buffer().slice(..), wgpu::IndexFormat::Uint32);
+
+        render_pass.set_bind_group(0, &camera_data.bind_group, &[]);
+        render_pass.set_bind_group(1, &data.building_data_bind_group, &[]);
+
+        for index_data in &data.instance_index {
+            let instance = index_data.instance_data_index;
+            render_pass.draw_indexed(index_data.indices_range.clone(), 0, instance..instance + 1);
+        }
+    }
+}
+
+fn create_render_pipeline(
+    device: &wgpu::Device,
+    layout: &PipelineLayout,
+    format: TextureFormat,
+    module: &ShaderModule,
+) -> RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("building Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module,
+            entry_point: "vs_main",
+            buffers: &[BuildingMeshVertex::decs()],
+        },
+
+        fragment: Some(wgpu::FragmentState {
+            module,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+
+        multiview: None,
+    })
+}
+
+// Keeps the old pipeline if the new source is broken
+fn on_shader_changed(storage: &mut Storage, event: &ShaderChanged) {
+    let (gpu, data, assets) = storage
+        .singletons
+        .get_many_mut::<(Gpu, BuildingRendererData, AssetStorage)>()
+        .unwrap();
+
+    if event.shader.get_id() != data.shader.get_id() {
+        return;
+    }
+
+    let pipeline = assets
+        .get_data(&data.shader)
+        .create_pipeline(&gpu.device, &event.path, |module| {
+            create_render_pipeline(
+                &gpu.device,
+                &data.render_pipeline_layout,
+                gpu.surface_config.format,
+                module,
+            )
+        });
+
+    if let Some(pipeline) = pipeline {
+        data.render_pipeline = pipeline;
+    }
+}
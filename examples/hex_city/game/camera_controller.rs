@@ -0,0 +1,87 @@
+use pixel_rs::app::PluginInfo;
+use pixel_rs::plugins::core::input::input_plugin::InputPlugin;
+use pixel_rs::plugins::core::camera_plugin::CameraPlugin;
+use pixel_rs::plugins::renderer_plugins::post_process::{
+    PostEffect, PostProcess, PostProcessPlugin, Vignette,
+};
+use pixel_rs::storage::Storage;
+use super::rules::{GameState, RulesPlugin};
+use pixel_rs::plugins::core::user_settings::UserSettings;
+use glam::Vec2;
+
+use pixel_rs::{
+    app::Plugin,
+    math::transform2d::Transform2d,
+    plugins::core::{
+        camera_plugin::{Camera, Viewport},
+        input::input_plugin::{Input, MouseButton},
+    },
+};
+
+pub struct CameraControllerPlugin;
+
+#[derive(Default)]
+pub struct CameraController {
+    move_start_offset: Vec2,
+    is_right_click_pressed: bool,
+}
+
+impl Plugin for CameraControllerPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<InputPlugin>(),
+            PluginInfo::of::<CameraPlugin>(),
+            PluginInfo::of::<PostProcessPlugin>(),
+            PluginInfo::of::<RulesPlugin>(),
+        ]
+    }
+
+    fn build(app: &mut pixel_rs::app::App) {
+        let camera = app.storage.world.find_by_name("main_camera").unwrap();
+        let post_process = PostProcess::new()
+            .with_effect("vignette", PostEffect::Vignette(Vignette::default()));
+
+        app.storage.world.add_component(camera, post_process);
+
+        app.add_system_in_state(
+            pixel_rs::app::SystemStage::Update,
+            GameState::Playing,
+            on_update,
+        );
+
+        app.storage.singletons.insert(CameraController::default());
+    }
+}
+
+fn on_update(world: &mut Storage) {
+    let (input, viewport, camera_controller, user_settings) = world
+        .singletons
+        .get_many_mut::<(Input, Viewport, CameraController, UserSettings)>()
+        .unwrap();
+
+    let pan_speed = user_settings.camera.pan_speed;
+
+    let current_position = input.mouse_position();
+
+    let is_right_pressed = input.is_mouse_button_pressed(MouseButton::Right);
+
+    if camera_controller.is_right_click_pressed != is_right_pressed {
+        if is_right_pressed {
+            camera_controller.move_start_offset = current_position;
+        }
+
+        camera_controller.is_right_click_pressed = is_right_pressed;
+    }
+
+    if is_right_pressed {
+        let delta = (current_position - camera_controller.move_start_offset)
+            * viewport.get_size()
+            * 0.5
+            * pan_speed;
+        camera_controller.move_start_offset = current_position;
+
+        let (transform2d, _) = world.world.query_mut::<(Transform2d, Camera)>().next().unwrap();
+
+        transform2d.position += delta;
+    }
+}
@@ -1,56 +1,57 @@
-use std::{env, path::Path, sync::Arc};
+use pixel_rs::app::PluginInfo;
+use pixel_rs::plugins::core::render_plugin::RenderPlugin;
+use pixel_rs::plugins::core::asset_storage::AssetStoragePlugin;
+use pixel_rs::plugins::renderer_plugins::tilemap_renderer::TileMapRenderer;
+use std::env;
 
 use glam::Vec2;
 
-use crate::{
+use pixel_rs::{
     app::Plugin,
-    ecs::{component::Component, world::World},
-    game::core::level_manager::TilesAddedEvent,
-    math::{
-        color::Color,
-        honeycomb::{Hextor, SpiralLoop},
-        transform2d::{self, Transform2d},
-    },
+    ecs::component::Component,
+    math::transform2d::Transform2d,
     plugins::{
         asset_types::image::Image,
-        core::{asset_storage::AssetStorage, render_plugin::Gpu, timer_plugin::Time},
-        renderer_plugins::{
-            mesh::Mesh,
-            sprite_renderer::Sprite,
-            texture::Texture,
-            tilemap_renderer::{TileData, TileMap},
-            // tilemap_renderer::{TileData, TileMap, TileMapBindGroupLayout},
-        },
+        core::{asset_storage::AssetStorage, render_plugin::Gpu},
+        renderer_plugins::tilemap_renderer::{TileData, TileMap},
     },
     storage::Storage,
 };
 
-use super::core::level_manager::LevelManager;
+use super::rules::level_manager::{LevelManager, TilesAddedEvent};
+
+const GRASS_TEXTURE_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/hex_city/game/assets/grass.png"
+);
+
+const ROAD_TEXTURE_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/hex_city/game/assets/road.png"
+);
 
 pub struct GroundPlugin;
 pub struct Ground;
 impl Component for Ground {}
 
 impl Plugin for GroundPlugin {
-    fn build(app: &mut crate::app::App) {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<RenderPlugin>(),
+            PluginInfo::of::<AssetStoragePlugin>(),
+            PluginInfo::of::<TileMapRenderer>(),
+        ]
+    }
+
+    fn build(app: &mut pixel_rs::app::App) {
         let (asset_storage, gpu) = app
             .storage
             .singletons
             .get_many_mut::<(AssetStorage, Gpu)>()
             .unwrap();
 
-        // Todo: Find out how to deal with paths
-        let grass_texture = asset_storage
-            .get::<Image>(
-                "/mnt/09cbb5c3-3c84-4ea4-b328-254e96041faf/pixel-rs/src/game/assets/grass.png",
-            )
-            .unwrap();
-
-        let road_texture = asset_storage
-            .get::<Image>(
-                "/mnt/09cbb5c3-3c84-4ea4-b328-254e96041faf/pixel-rs/src/game/assets/road.png",
-            )
-            .unwrap();
+        let grass_texture = asset_storage.get::<Image>(GRASS_TEXTURE_PATH).unwrap();
+        let road_texture = asset_storage.get::<Image>(ROAD_TEXTURE_PATH).unwrap();
 
         let data = asset_storage.get_data(&road_texture).get_data().clone();
         let grass_texture_data = asset_storage.get_data(&grass_texture).get_data().clone();
@@ -0,0 +1,73 @@
+use pixel_rs::app::{Plugin, SystemStage};
+use pixel_rs::plugins::core::input::input_plugin::Input;
+use pixel_rs::plugins::core::render_plugin::ScreenshotRequests;
+use pixel_rs::plugins::core::user_settings::UserSettings;
+use pixel_rs::plugins::core::window::window_settings::WindowSettings;
+use pixel_rs::storage::Storage;
+
+use self::building_mesh::BuildingRenderPlugin;
+use self::camera_controller::CameraControllerPlugin;
+use self::ground::GroundPlugin;
+use self::road::RoadPlugin;
+use self::road_placer::RoadPlacerPlugin;
+use self::rules::{is_key_event, RulesPlugin};
+
+mod building_mesh;
+mod camera_controller;
+mod ground;
+mod road;
+mod road_placer;
+// Buildings, undo and scoring are not hooked up to the game yet
+#[allow(dead_code)]
+mod rules;
+
+// The rules plugin has the game logic, everything else here draws it
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(app: &mut pixel_rs::app::App) {
+        app.schedular.add_system(SystemStage::Input, toggle_fullscreen);
+        app.schedular.add_system(SystemStage::Input, take_screenshot);
+
+        app.register_plugin::<RulesPlugin>();
+        app.register_plugin::<GroundPlugin>();
+        app.register_plugin::<RoadPlugin>();
+        app.register_plugin::<RoadPlacerPlugin>();
+        app.register_plugin::<CameraControllerPlugin>();
+        app.register_plugin::<BuildingRenderPlugin>();
+    }
+}
+
+fn toggle_fullscreen(storage: &mut Storage) {
+    let (input, user_settings, window_settings) = storage
+        .singletons
+        .get_many_mut::<(Input, UserSettings, WindowSettings)>()
+        .unwrap();
+
+    if is_key_event(input, user_settings.key_bindings.fullscreen) {
+        window_settings.toggle_fullscreen();
+    }
+}
+
+fn take_screenshot(storage: &mut Storage) {
+    let (input, user_settings, screenshots) = storage
+        .singletons
+        .get_many_mut::<(Input, UserSettings, ScreenshotRequests)>()
+        .unwrap();
+
+    if !is_key_event(input, user_settings.key_bindings.screenshot) {
+        return;
+    }
+
+    if let Err(err) = std::fs::create_dir_all("screenshots") {
+        log::error!("Could not create the screenshots folder: {err}");
+        return;
+    }
+
+    let time = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+
+    screenshots.request(format!("screenshots/screenshot-{time}.png"));
+}
@@ -0,0 +1,169 @@
+use pixel_rs::app::PluginInfo;
+use pixel_rs::plugins::core::render_plugin::RenderPlugin;
+use pixel_rs::plugins::core::asset_storage::AssetStoragePlugin;
+use pixel_rs::plugins::renderer_plugins::sprite_renderer::SpritePlugin;
+
+use glam::Vec2;
+
+use pixel_rs::{
+    app::Plugin,
+    ecs::{
+        component::Component,
+        hex_index::HexPosition,
+    },
+    math::{
+        honeycomb::{Hextor, SpiralLoop},
+        transform2d::Transform2d,
+    },
+    plugins::{
+        asset_types::{
+            image::Image,
+            prefab::{Prefab, PrefabOverrides},
+        },
+        core::{
+            asset_storage::AssetStorage,
+            render_plugin::Gpu,
+        },
+    },
+    storage::Storage,
+};
+
+use super::rules::level_manager::{LevelManager, RoadAddedEvent, RoadRemovedEvent};
+
+const ROAD_PREFAB_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/hex_city/game/assets/road.prefab.toml"
+);
+
+// Same path the prefab's `road.png` resolves to, so the sprites find this texture
+const ROAD_TEXTURE_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/hex_city/game/assets/road.png"
+);
+
+pub struct RoadPlugin;
+pub struct Roads;
+
+#[derive(Debug)]
+pub struct Road {
+    center: Hextor,
+    neighbor: Hextor,
+}
+
+impl Component for Road {}
+impl Component for Roads {}
+
+impl Plugin for RoadPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<RenderPlugin>(),
+            PluginInfo::of::<AssetStoragePlugin>(),
+            PluginInfo::of::<SpritePlugin>(),
+        ]
+    }
+
+    fn build(app: &mut pixel_rs::app::App) {
+        app.storage.world.register_component::<Road>();
+        app.storage.world.register_component::<Roads>();
+
+        let (asset_storage, gpu) = app
+            .storage
+            .singletons
+            .get_many_mut::<(AssetStorage, Gpu)>()
+            .unwrap();
+
+        let road_texture = asset_storage.get::<Image>(ROAD_TEXTURE_PATH).unwrap();
+
+        let data = asset_storage.get_data(&road_texture);
+
+        gpu.create_texture(
+            road_texture.get_id(),
+            "Road Texture",
+            data.get_data(),
+            64,
+            9,
+        );
+
+        app.storage.add_listener::<RoadAddedEvent>(on_road_added);
+        app.storage
+            .add_listener::<RoadRemovedEvent>(on_road_removed);
+    }
+}
+
+fn on_road_added(storage: &mut Storage, data: &RoadAddedEvent) {
+    let (asset_storage, level_manager) = storage
+        .singletons
+        .get_many_mut::<(AssetStorage, LevelManager)>()
+        .unwrap();
+
+    let Some(road_prefab) = asset_storage.get::<Prefab>(ROAD_PREFAB_PATH) else {
+        return;
+    };
+
+    let center = data.new_road;
+
+    let center_pos: Vec2 = center.to_vector(32.0).into();
+
+    // Spawned after the loop, spawning needs the whole storage
+    let mut halves = Vec::new();
+
+    for neighbor in SpiralLoop::new(center, 1) {
+        if level_manager.is_road(&neighbor) {
+            if neighbor == center {
+                continue;
+            }
+
+            let neighbor_pos: Vec2 = neighbor.to_vector(32.0).into();
+
+            let center_transform = Transform2d::new(
+                center_pos,
+                (neighbor_pos - center_pos).to_angle(),
+                Vec2::new(1.0, 1.0),
+            );
+
+            let neighbor_transform = Transform2d::new(
+                neighbor_pos,
+                (center_pos - neighbor_pos).to_angle(),
+                Vec2::new(1.0, 1.0),
+            );
+
+            halves.push(
+                PrefabOverrides::new()
+                    .with(center_transform)
+                    .with(HexPosition::new(center))
+                    .with(Road { center, neighbor }),
+            );
+
+            halves.push(
+                PrefabOverrides::new()
+                    .with(neighbor_transform)
+                    .with(HexPosition::new(neighbor))
+                    .with(Road { center, neighbor })
+                    .set("Sprite", "z_index", 100),
+            );
+        }
+    }
+
+    for overrides in halves {
+        storage.spawn_prefab(&road_prefab, overrides);
+    }
+}
+
+fn on_road_removed(storage: &mut Storage, data: &RoadRemovedEvent) {
+    // find roads and remove them lol
+
+    let mut entities_to_delete = Vec::new();
+
+    // Both halves of a road touching the removed tile are at most one tile away from it
+    for entity_id in storage.world.entities_in_range(data.road, 1) {
+        if let Some(road) = storage.world.get_component::<Road>(entity_id) {
+            if road.center == data.road || road.neighbor == data.road {
+                entities_to_delete.push(entity_id);
+            }
+        }
+    }
+
+    for delete_entity in entities_to_delete {
+        storage.world.remove_entity(delete_entity);
+    }
+}
@@ -0,0 +1,129 @@
+use pixel_rs::app::PluginInfo;
+use pixel_rs::plugins::core::asset_storage::AssetStoragePlugin;
+use pixel_rs::plugins::core::render_plugin::RenderPlugin;
+use pixel_rs::plugins::other::tweener::TweenerPlugin;
+use pixel_rs::plugins::renderer_plugins::debug_draw::DebugDrawPlugin;
+use pixel_rs::plugins::renderer_plugins::sprite_renderer::SpritePlugin;
+use pixel_rs::{
+    ecs::{component::Component, name::Name},
+    storage::Storage,
+};
+
+use glam::Vec2;
+
+use pixel_rs::{
+    app::Plugin,
+    math::{color::Color, honeycomb::Hextor, transform2d::Transform2d},
+    plugins::{
+        asset_types::image::Image,
+        core::{asset_storage::AssetStorage, render_plugin::Gpu},
+        other::tweener::{self, PositionTweener},
+        renderer_plugins::{debug_draw::DebugDraw, sprite_renderer::Sprite},
+    },
+};
+
+use super::rules::road_placement::{RoadCursor, RoadPlacementPlugin};
+use super::rules::GameState;
+
+const SELECTOR_TEXTURE_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/hex_city/game/assets/selector.png"
+);
+
+// The selector sprite, it tweens to the RoadCursor tile
+pub struct RoadPlacer {
+    current_pos: Hextor,
+}
+impl Component for RoadPlacer {}
+
+pub struct RoadPlacerPlugin;
+
+impl Plugin for RoadPlacerPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<RenderPlugin>(),
+            PluginInfo::of::<AssetStoragePlugin>(),
+            PluginInfo::of::<SpritePlugin>(),
+            PluginInfo::of::<TweenerPlugin>(),
+            PluginInfo::of::<DebugDrawPlugin>(),
+            PluginInfo::of::<RoadPlacementPlugin>(),
+        ]
+    }
+
+    fn build(app: &mut pixel_rs::app::App) {
+        app.storage.world.register_component::<RoadPlacer>();
+
+        let (asset_storage, gpu) = app
+            .storage
+            .singletons
+            .get_many_mut::<(AssetStorage, Gpu)>()
+            .unwrap();
+
+        let select_sprite = asset_storage.get::<Image>(SELECTOR_TEXTURE_PATH).unwrap();
+
+        let data = asset_storage.get_data(&select_sprite);
+        gpu.create_texture(
+            select_sprite.get_id(),
+            "Selector sprite",
+            data.get_data(),
+            64,
+            74,
+        );
+
+        let sprite = Sprite::new(
+            select_sprite.clone(),
+            Color::new(1.0, 1.0, 1.0, 1.0),
+            Vec2::new(64.0, 74.0),
+            2,
+        );
+
+        let transform2d = Transform2d::IDENTITY;
+
+        let road_placer = RoadPlacer {
+            current_pos: Hextor::new(0, 0),
+        };
+
+        let tweener = PositionTweener::default();
+
+        app.storage.world.insert_entity((
+            Name::new("road_placer"),
+            sprite,
+            transform2d,
+            road_placer,
+            tweener,
+        ));
+
+        app.add_system_in_state(
+            pixel_rs::app::SystemStage::Update,
+            GameState::Playing,
+            on_update,
+        );
+    }
+}
+
+fn on_update(storage: &mut Storage) {
+    let hex_pos = storage.singletons.get::<RoadCursor>().unwrap().tile;
+
+    let (transform2d, road_placer, tweener) = match storage
+        .world
+        .query_mut_single::<(Transform2d, RoadPlacer, PositionTweener)>()
+    {
+        Ok(road_placer) => road_placer,
+        Err(err) => {
+            log::error!("{err}");
+            return;
+        }
+    };
+
+    if road_placer.current_pos != hex_pos {
+        road_placer.current_pos = hex_pos;
+
+        let end = hex_pos.to_vector(32.0).into();
+
+        tweener.tween(transform2d.position, end, 0.05, tweener::Easing::Linear);
+    }
+
+    // Tile under the mouse, the selector sprite lags behind it while tweening
+    let debug_draw = storage.singletons.get_mut::<DebugDraw>().unwrap();
+    debug_draw.hexagon(hex_pos, 32.0, Color::new(1.0, 1.0, 0.0, 1.0));
+}
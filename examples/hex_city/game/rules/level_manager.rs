@@ -1,16 +1,14 @@
 use hashbrown::{HashMap, HashSet};
 
-use crate::{
+use pixel_rs::{
     ecs::event_bus::WorldEvent,
-    game::{
-        resources::{
-            building_descriptor::BuildingDescriptor,
-            level_descriptors::{self, LevelDescriptor},
-            resource_stack::{self, GameResource, ResourceStack},
-        },
-        road,
-    },
-    math::honeycomb::{self, Hextor, SpiralLoop},
+    math::honeycomb::{self, Hextor},
+};
+
+use super::resources::{
+    building_descriptor::BuildingDescriptor,
+    level_descriptors::LevelDescriptor,
+    resource_stack::{GameResource, ResourceStack},
 };
 
 pub struct InventoryManager {
@@ -120,7 +118,7 @@ impl Buildings {
     }
 
     pub fn is_empty(&self, tile: &Hextor) -> bool {
-        return !self.tile_map.contains_key(tile);
+        !self.tile_map.contains_key(tile)
     }
 }
 
@@ -233,7 +231,7 @@ impl UndoRedo {
         let action = self.undo_stack.pop().unwrap();
         self.redo_stack.push(action.clone());
 
-        return action;
+        action
     }
 
     pub fn get_redo_action(&mut self) -> Action {
@@ -241,15 +239,15 @@ impl UndoRedo {
 
         self.undo_stack.push(action.clone());
 
-        return action;
+        action
     }
 
     pub fn is_undo_empty(&self) -> bool {
-        self.undo_stack.len() == 0
+        self.undo_stack.is_empty()
     }
 
     pub fn is_redo_empty(&self) -> bool {
-        self.redo_stack.len() == 0
+        self.redo_stack.is_empty()
     }
 }
 
@@ -306,15 +304,15 @@ impl LevelManager {
     pub fn undo(&mut self) {
         let action = self.undo_redo.get_undo_action();
 
-        if let Some(building_instance) = action.building {
+        if let Some(_building_instance) = action.building {
             // Remove that building
         }
 
-        if let Some(Hextor) = action.road {
+        if let Some(_road) = action.road {
             // Remove that road
         }
 
-        if let Some(tiles) = action.tiles_added {
+        if let Some(_tiles) = action.tiles_added {
             // Remove those tiles
         }
 
@@ -325,15 +323,15 @@ impl LevelManager {
     pub fn redo(&mut self) {
         let action = self.undo_redo.get_redo_action();
 
-        if let Some(building_instance) = action.building {
+        if let Some(_building_instance) = action.building {
             // add that building
         }
 
-        if let Some(Hextor) = action.road {
+        if let Some(_road) = action.road {
             // add that road
         }
 
-        if let Some(tiles) = action.tiles_added {
+        if let Some(_tiles) = action.tiles_added {
             // add those tiles
         }
 
@@ -389,7 +387,7 @@ impl LevelManager {
     pub fn can_place_building(&self, building: BuildingInstance) -> BuildingPlaceQueryResult {
         let mut can_place = true;
         let mut not_empty_tiles = Vec::<Hextor>::new(); // tiles which are not empty
-        let mut score_change = building.descriptor.base_score;
+        let score_change = building.descriptor.base_score;
         let mut is_connected = false;
 
         for tile in building.rotated_tiles {
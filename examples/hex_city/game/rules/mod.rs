@@ -0,0 +1,99 @@
+// Game rules without any rendering, so they also run headless. tests/hex_city.rs includes this
+// folder with #[path], keep the paths in here relative (`super::`) instead of `crate::game::`.
+
+use pixel_rs::app::{App, Plugin, SystemStage};
+use pixel_rs::math::honeycomb::Hextor;
+use pixel_rs::plugins::core::input::input_plugin::{
+    ButtonState, Input, InputEvent, KeyEvent, KeyboardEvent,
+};
+use pixel_rs::plugins::core::input::keycode::KeyCode;
+use pixel_rs::plugins::core::user_settings::UserSettings;
+use pixel_rs::state::{NextState, State};
+use pixel_rs::storage::Storage;
+
+use self::level_manager::{LevelManager, RoadAddedEvent, TilesAddedEvent};
+use self::resources::level_descriptors::get_dummy_level;
+use self::road_placement::RoadPlacementPlugin;
+
+pub mod level_manager;
+pub mod resources;
+pub mod road_placement;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameState {
+    Playing,
+    Paused,
+}
+
+pub struct RulesPlugin;
+
+impl Plugin for RulesPlugin {
+    fn build(app: &mut App) {
+        // Has to exist before the other game plugins add their in_state systems
+        app.add_state(GameState::Playing);
+        app.schedular.add_system(SystemStage::Input, toggle_pause);
+
+        app.register_plugin::<LevelPlugin>();
+        app.register_plugin::<RoadPlacementPlugin>();
+    }
+}
+
+// Loads the level. The events are sent in Start, after the ground and road plugins started listening
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(app: &mut App) {
+        let level_descriptor = get_dummy_level();
+        let level_manager = LevelManager::new(&level_descriptor);
+        app.storage.singletons.insert(level_manager);
+
+        app.schedular.add_system(SystemStage::Start, start_level);
+    }
+}
+
+fn start_level(storage: &mut Storage) {
+    storage.emit(TilesAddedEvent);
+
+    storage.emit(RoadAddedEvent {
+        new_road: Hextor::new(0, 0),
+    });
+
+    let level_manager = storage.singletons.get_mut::<LevelManager>().unwrap();
+    level_manager.place_road(Hextor::new(1, 0));
+
+    storage.emit(RoadAddedEvent {
+        new_road: Hextor::new(1, 0),
+    })
+}
+
+pub fn is_key_event(input: &Input, key: KeyCode) -> bool {
+    matches!(
+        input.last_event,
+        Some(InputEvent::KeyboardEvent(KeyboardEvent::KeyEvent(KeyEvent {
+            button,
+            state: ButtonState::Pressed,
+        }))) if button == key
+    )
+}
+
+fn toggle_pause(storage: &mut Storage) {
+    let (input, user_settings) = storage
+        .singletons
+        .get_many::<(Input, UserSettings)>()
+        .unwrap();
+
+    if !is_key_event(input, user_settings.key_bindings.pause) {
+        return;
+    }
+
+    let next = match storage.singletons.get::<State<GameState>>().unwrap().get() {
+        GameState::Playing => GameState::Paused,
+        GameState::Paused => GameState::Playing,
+    };
+
+    storage
+        .singletons
+        .get_mut::<NextState<GameState>>()
+        .unwrap()
+        .set(next);
+}
@@ -1,4 +1,4 @@
-use crate::math::honeycomb::Hextor;
+use pixel_rs::math::honeycomb::Hextor;
 
 use super::{neighborhood_effect::NeighborhoodEffect, resource_stack::ResourceStack};
 
@@ -1,4 +1,4 @@
-use crate::math::honeycomb::{Hextor, SpiralLoop};
+use pixel_rs::math::honeycomb::{Hextor, SpiralLoop};
 
 use super::resource_stack::ResourceStack;
 
@@ -27,24 +27,21 @@ pub fn get_dummy_level() -> LevelDescriptor {
         Hextor::new(2, 4),
     ];
 
-    let mut i = 0;
-    for center in centers {
+    for (i, center) in centers.into_iter().enumerate() {
         let tiles: Vec<Hextor> = SpiralLoop::new(center, 5).collect();
 
         let area = AreaDescriptor {
             reward: ResourceStack::resource_array(3, 0, 0),
-            unlock_score: i * 3,
-            tiles: tiles,
+            unlock_score: i as u32 * 3,
+            tiles,
         };
 
         areas.push(area);
-
-        i += 1;
     }
 
     LevelDescriptor {
         // starting_resources: ResourceStack::resource_array(0, 0, 0),
-        areas: areas,
+        areas,
         starting_road: Hextor::new(0, 0),
     }
 }
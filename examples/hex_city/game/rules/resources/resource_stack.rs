@@ -1,3 +1,5 @@
+// MAX is the resource count, not a resource
+#[allow(clippy::upper_case_acronyms)]
 #[derive(PartialEq, Eq, Hash, Clone, Copy)]
 pub enum GameResource {
     Dood,
@@ -0,0 +1,107 @@
+use pixel_rs::{
+    app::{App, Plugin, PluginInfo, SystemStage},
+    math::honeycomb::Hextor,
+    plugins::core::{
+        camera_plugin::{CameraPlugin, Viewport},
+        input::{
+            input_plugin::{Input, InputPlugin, MouseButton},
+            keycode::KeyCode,
+        },
+    },
+    storage::Storage,
+};
+
+use super::{
+    level_manager::{LevelManager, RoadAddedEvent, RoadRemovedEvent},
+    GameState,
+};
+
+// Tile roads are placed on and removed from, follows the mouse. The selector sprite shows it.
+pub struct RoadCursor {
+    pub tile: Hextor,
+}
+
+pub struct RoadPlacementPlugin;
+
+impl Plugin for RoadPlacementPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<InputPlugin>(),
+            PluginInfo::of::<CameraPlugin>(),
+        ]
+    }
+
+    fn build(app: &mut App) {
+        app.storage.singletons.insert(RoadCursor {
+            tile: Hextor::new(0, 0),
+        });
+
+        app.add_system_in_state(SystemStage::Input, GameState::Playing, on_input);
+        app.add_system_in_state(SystemStage::Update, GameState::Playing, on_update);
+    }
+}
+
+fn on_update(storage: &mut Storage) {
+    let (input, viewport, cursor) = storage
+        .singletons
+        .get_many_mut::<(Input, Viewport, RoadCursor)>()
+        .unwrap();
+
+    let world_mouse_pos = viewport.screen_to_world(input.mouse_position());
+    cursor.tile = Hextor::from_vector(world_mouse_pos.x, world_mouse_pos.y, 32.0);
+
+    let tile = cursor.tile;
+
+    if input.is_mouse_button_pressed(MouseButton::Left) {
+        place_road(storage, tile);
+    } else if input.is_mouse_button_pressed(MouseButton::Right) {
+        remove_road(storage, tile);
+    }
+}
+
+fn on_input(storage: &mut Storage) {
+    let (input, cursor) = storage
+        .singletons
+        .get_many_mut::<(Input, RoadCursor)>()
+        .unwrap();
+
+    if input.is_key_pressed(KeyCode::KeyW) {
+        cursor.tile.r += 1;
+    }
+
+    if input.is_key_pressed(KeyCode::KeyS) {
+        cursor.tile.r -= 1;
+    }
+
+    if input.is_key_pressed(KeyCode::KeyA) {
+        cursor.tile.q -= 1;
+    }
+
+    if input.is_key_pressed(KeyCode::KeyD) {
+        cursor.tile.q += 1;
+    }
+
+    let tile = cursor.tile;
+
+    if input.is_key_pressed(KeyCode::Space) {
+        place_road(storage, tile);
+    }
+}
+
+fn place_road(storage: &mut Storage, tile: Hextor) {
+    let level_manager = storage.singletons.get_mut::<LevelManager>().unwrap();
+
+    if level_manager.can_place_road(&tile) {
+        level_manager.place_road(tile);
+        storage.emit(RoadAddedEvent { new_road: tile });
+    }
+}
+
+fn remove_road(storage: &mut Storage, tile: Hextor) {
+    let level_manager = storage.singletons.get_mut::<LevelManager>().unwrap();
+
+    if level_manager.is_road(&tile) {
+        level_manager.remove_road(tile);
+        storage.emit(RoadRemovedEvent { road: tile });
+    }
+}
@@ -0,0 +1,36 @@
+
+use pixel_rs::{
+    app::App,
+    plugins::{
+        core::{camera_plugin::CameraPlugin, CorePlugins},
+        other::{diagnostics::DiagnosticsPlugin, tweener::TweenerPlugin},
+        renderer_plugins::Renderer2dPlugin,
+    },
+};
+
+use game::GamePlugin;
+
+mod game;
+
+fn main() {
+    env_logger::builder()
+        // setting this to None disables the timestamp
+        .format_timestamp(Some(env_logger::TimestampPrecision::Seconds))
+        .init();
+
+    let mut app = App::new();
+
+    // Core plugins
+    app.register_plugin::<CorePlugins>();
+
+    app.register_plugin::<CameraPlugin>();
+    app.register_plugin::<TweenerPlugin>();
+    app.register_plugin::<DiagnosticsPlugin>();
+
+    app.register_plugin::<Renderer2dPlugin>();
+
+    // Game related plugins are added into [game/mod.rs]
+    app.register_plugin::<GamePlugin>();
+
+    app.run();
+}
@@ -1,20 +1,31 @@
-use winit::dpi::PhysicalSize;
+use std::any::TypeId;
+
+use hashbrown::HashSet;
+use log::{debug, info, warn};
 
 use crate::{
-    ecs::{world::{Schedular, World}, singletons::Singletons, event_bus::EventBus},
-    plugins::core::render_plugin::Renderer, storage::Storage,
+    ecs::{world::Schedular, event_bus::WorldEvent},
+    plugins::core::window::window_events::{WindowMinimized, WindowResized, WindowState},
+    state::{self, NextState, State, StateLabel, StateSystems},
+    storage::Storage,
 };
 
+#[cfg(feature = "render")]
+use crate::plugins::core::render_graph::RenderGraph;
+
 // Order is
-// PreUpdate -> Update -> PreRender -> Render
+// StateTransition -> PreUpdate -> Update -> PreRender -> Render
 // PreInput -> Input. Input is called by winit. Not sure in which order but most likely before PreUpdate
 // Resize is called by winit
 // Start is called only once on startup
+// Shutdown is called only once, right before the app exits
 
-#[derive(Hash, PartialEq, Eq, Clone, Copy)]
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
 pub enum SystemStage {
     Start,
 
+    StateTransition,
+
     Resize,
 
     PreInput,
@@ -25,47 +36,176 @@ pub enum SystemStage {
 
     PreRender,
     Render,
+
+    Shutdown,
 }
 
-pub trait Plugin {
+// Any system can emit this, the runner finishes the current frame, runs Shutdown and exits
+#[derive(Debug, Clone, Copy)]
+pub struct AppExit;
+
+impl WorldEvent for AppExit {}
+
+#[derive(Default)]
+struct AppExitState {
+    requested: bool,
+    shut_down: bool,
+}
+
+fn on_app_exit(storage: &mut Storage, _: &AppExit) {
+    storage.singletons.get_mut::<AppExitState>().unwrap().requested = true;
+}
+
+pub trait Plugin: 'static {
     fn build(app: &mut App);
+
+    fn name() -> &'static str {
+        let name = std::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
+    // Plugins that have to be built before this one, e.g. anything using Gpu in build needs RenderPlugin.
+    // They still have to be registered by someone, this only decides the order.
+    fn dependencies() -> Vec<PluginInfo> {
+        Vec::new()
+    }
+
+    // Built before this one if they are registered, but not required
+    fn optional_dependencies() -> Vec<PluginInfo> {
+        Vec::new()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct PluginInfo {
+    type_id: TypeId,
+    name: &'static str,
+    build: fn(&mut App),
+    dependencies: fn() -> Vec<PluginInfo>,
+    optional_dependencies: fn() -> Vec<PluginInfo>,
+}
+
+impl PluginInfo {
+    pub fn of<T: Plugin>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            name: T::name(),
+            build: T::build,
+            dependencies: T::dependencies,
+            optional_dependencies: T::optional_dependencies,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Plugins {
+    registered: HashSet<TypeId>,
+    built: HashSet<TypeId>,
+    pending: Vec<PluginInfo>,
 }
 
 pub struct App {
     pub storage: Storage,
     pub schedular: Schedular<SystemStage, Storage>,
-    pub renderers: Vec<Box<dyn Renderer>>,
+    #[cfg(feature = "render")]
+    pub render_graph: RenderGraph,
     pub runner: fn(App),
-    pub render_function: fn(&mut Storage, &Vec<Box<dyn Renderer>>), // SO I dont know why I had this anymore lol
+    #[cfg(feature = "render")]
+    pub render_function: fn(&mut Storage, &RenderGraph), // SO I dont know why I had this anymore lol
+    plugins: Plugins,
+    started: bool,
 }
 
 impl App {
     pub fn new() -> Self {
+        let mut storage = Storage::new();
+        storage.singletons.insert(AppExitState::default());
+        storage.add_listener::<AppExit>(on_app_exit);
+
         Self {
-            storage: Storage::new(),
+            storage,
             schedular: Schedular::new(),
             runner: |_: App| {},
+            #[cfg(feature = "render")]
             render_function: |_, _| {},
-            renderers: Vec::new(),
+            #[cfg(feature = "render")]
+            render_graph: RenderGraph::new(),
+            plugins: Plugins::default(),
+            started: false,
         }
     }
 
-    pub fn run(self) {
+    pub fn run(mut self) {
+        self.build_plugins();
         (self.runner)(self);
     }
 
     pub fn update(&mut self) {
+        // Every plugin is built by now, so Start systems can emit events the others listen to
+        if !self.started {
+            self.started = true;
+            self.schedular.run(SystemStage::Start, &mut self.storage);
+        }
+
+        self.schedular
+            .run(SystemStage::StateTransition, &mut self.storage);
+
         self.schedular.run(SystemStage::PreUpdate, &mut self.storage);
         self.schedular.run(SystemStage::Update, &mut self.storage);
 
         self.schedular.run(SystemStage::PreRender, &mut self.storage);
-        let fun = self.render_function;
-        let world = &mut self.storage;
 
-        (fun)(world, &self.renderers);
+        #[cfg(feature = "render")]
+        {
+            let fun = self.render_function;
+            let world = &mut self.storage;
+
+            (fun)(world, &self.render_graph);
+        }
+    }
+
+    pub fn exit_requested(&self) -> bool {
+        self.storage
+            .singletons
+            .get::<AppExitState>()
+            .unwrap()
+            .requested
+    }
+
+    // Safe to call more than once, Shutdown systems only run the first time
+    pub fn shutdown(&mut self) {
+        let exit_state = self.storage.singletons.get_mut::<AppExitState>().unwrap();
+
+        if exit_state.shut_down {
+            return;
+        }
+
+        exit_state.shut_down = true;
+
+        info!("Shutting down");
+        self.schedular.run(SystemStage::Shutdown, &mut self.storage);
     }
 
-    pub fn on_resize(&mut self, physical_size: PhysicalSize<u32>) {
+    pub fn on_resize(&mut self, width: u32, height: u32) {
+        let window_state = self.storage.singletons.get_mut::<WindowState>().unwrap();
+        window_state.width = width;
+        window_state.height = height;
+
+        let minimized = width == 0 || height == 0;
+        let minimized_changed = window_state.minimized != minimized;
+        window_state.minimized = minimized;
+
+        self.storage.emit(WindowResized { width, height });
+
+        if minimized_changed {
+            self.storage.emit(WindowMinimized { minimized });
+        }
+
+        // Nothing can be rendered into a 0x0 surface, Resize systems get called again on restore
+        if minimized {
+            return;
+        }
+
         self.schedular.run(SystemStage::Resize, &mut self.storage);
     }
 
@@ -83,17 +223,141 @@ impl App {
         // Nothing to do??
     }
 
+    // Plugins are not built right away, build_plugins does that once everything is registered
     pub fn register_plugin<T: Plugin>(&mut self) {
-        T::build(self);
+        let plugin = PluginInfo::of::<T>();
+
+        if !self.plugins.registered.insert(plugin.type_id) {
+            warn!("Plugin {} is registered twice, ignoring it", plugin.name);
+            return;
+        }
+
+        self.plugins.pending.push(plugin);
+    }
+
+    pub fn is_plugin_registered<T: Plugin>(&self) -> bool {
+        self.plugins.registered.contains(&TypeId::of::<T>())
+    }
+
+    // Builds in registration order, except that a plugin waits until all of its dependencies are built.
+    // Plugins registered inside build are picked up in the same loop.
+    pub fn build_plugins(&mut self) {
+        loop {
+            let ready = self.plugins.pending.iter().position(|plugin| {
+                let dependencies_built = (plugin.dependencies)()
+                    .iter()
+                    .all(|dependency| self.plugins.built.contains(&dependency.type_id));
+
+                let optional_dependencies_built = (plugin.optional_dependencies)()
+                    .iter()
+                    .all(|dependency| {
+                        self.plugins.built.contains(&dependency.type_id)
+                            || !self.plugins.registered.contains(&dependency.type_id)
+                    });
+
+                dependencies_built && optional_dependencies_built
+            });
+
+            let Some(index) = ready else {
+                break;
+            };
+
+            let plugin = self.plugins.pending.remove(index);
+            debug!("Building plugin {}", plugin.name);
+
+            (plugin.build)(self);
+            self.plugins.built.insert(plugin.type_id);
+        }
+
+        if self.plugins.pending.is_empty() {
+            return;
+        }
+
+        // "blocked" means registered but waiting on something itself, either missing or a cycle
+        let mut message = String::from("Could not build plugins:");
+
+        for plugin in self.plugins.pending.iter() {
+            let optional_dependencies = (plugin.optional_dependencies)()
+                .into_iter()
+                .filter(|dependency| self.plugins.registered.contains(&dependency.type_id));
+
+            let missing: Vec<String> = (plugin.dependencies)()
+                .into_iter()
+                .chain(optional_dependencies)
+                .filter(|dependency| !self.plugins.built.contains(&dependency.type_id))
+                .map(|dependency| {
+                    if self.plugins.registered.contains(&dependency.type_id) {
+                        format!("{} (blocked)", dependency.name)
+                    } else {
+                        format!("{} (not registered)", dependency.name)
+                    }
+                })
+                .collect();
+
+            message += &format!("\n  {} is missing {}", plugin.name, missing.join(", "));
+        }
+
+        panic!("{message}");
     }
 
     pub fn set_runner(&mut self, fun: fn(App)) {
         self.runner = fun;
     }
 
-    pub fn set_renderer(&mut self, fun: fn(&mut Storage, &Vec<Box<dyn Renderer>>)) {
+    #[cfg(feature = "render")]
+    pub fn set_renderer(&mut self, fun: fn(&mut Storage, &RenderGraph)) {
         self.render_function = fun;
     }
+
+    // on_enter of the initial state runs before the first frame
+    pub fn add_state<S: StateLabel>(&mut self, initial: S) {
+        if self.storage.singletons.get::<State<S>>().is_some() {
+            return;
+        }
+
+        self.storage.singletons.insert(State::new(initial));
+        self.storage.singletons.insert(NextState::<S>::new());
+        self.storage.singletons.insert(StateSystems::<S>::new());
+
+        self.schedular.add_system(
+            SystemStage::StateTransition,
+            state::apply_state_transition::<S>,
+        );
+
+        self.schedular
+            .add_system(SystemStage::Input, state::run_in_state_input::<S>);
+        self.schedular
+            .add_system(SystemStage::PreUpdate, state::run_in_state_pre_update::<S>);
+        self.schedular
+            .add_system(SystemStage::Update, state::run_in_state_update::<S>);
+        self.schedular
+            .add_system(SystemStage::PreRender, state::run_in_state_pre_render::<S>);
+    }
+
+    fn get_state_systems<S: StateLabel>(&mut self) -> &mut StateSystems<S> {
+        self.storage
+            .singletons
+            .get_mut::<StateSystems<S>>()
+            .expect("add_state must be called before adding state systems")
+    }
+
+    pub fn add_system_on_enter<S: StateLabel>(&mut self, state: S, fun: fn(&mut Storage)) {
+        self.get_state_systems::<S>().add_on_enter(state, fun);
+    }
+
+    pub fn add_system_on_exit<S: StateLabel>(&mut self, state: S, fun: fn(&mut Storage)) {
+        self.get_state_systems::<S>().add_on_exit(state, fun);
+    }
+
+    // Runs fun in the given stage only while State<S> is `state`
+    pub fn add_system_in_state<S: StateLabel>(
+        &mut self,
+        stage: SystemStage,
+        state: S,
+        fun: fn(&mut Storage),
+    ) {
+        self.get_state_systems::<S>().add_in_state(stage, state, fun);
+    }
 }
 
 impl Default for App {
@@ -1,9 +1,7 @@
 use hashbrown::HashMap;
-use std::any::{Any, TypeId};
 use std::fmt::Debug;
 
-use super::component::{Component, ComponentTypeId, TypeErasedComponentVec};
-use super::entity::EntityId;
+use super::component::{ComponentTypeId, TypeErasedComponentVec};
 
 #[derive(Debug)]
 pub struct Archetype {
@@ -71,9 +69,7 @@ impl Archetype {
         for id in vec {
             let index = *self.component_id_column_index_map.get(id).unwrap();
 
-            unsafe {
-                result.push(&self.columns[index]);
-            }
+            result.push(&self.columns[index]);
         }
 
         result
@@ -13,13 +13,13 @@ impl BitSet {
     };
 
     pub fn new() -> Self {
-        Self { bitmask: [0u64; 4] }
+        Self::EMPTY
     }
 
     pub fn insert_id(&mut self, id: u8) -> &mut Self {
         let index = id / 64;
         let position = id - index * 64;
-        self.bitmask[index as usize] = self.bitmask[index as usize] | 1 << position;
+        self.bitmask[index as usize] |= 1 << position;
 
         self
     }
@@ -1,18 +1,6 @@
-use bytemuck::Pod;
 use hashbrown::HashMap;
-use std::{
-    alloc::Layout,
-    any::{Any, TypeId},
-    marker::PhantomData,
-    mem::MaybeUninit,
-    sync::Arc,
-};
-
-use super::{
-    archetype::{self, Archetype},
-    bitset::{self, BitSet},
-    entity::{self, EntityId},
-};
+use std::any::{Any, TypeId};
+
 
 #[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
 pub struct ComponentTypeId(pub u8);
@@ -48,7 +36,7 @@ impl Components {
     }
 
     pub fn get_component_id(&self, type_id: &TypeId) -> Option<ComponentTypeId> {
-        self.type_map.get(type_id).map(|f| *f)
+        self.type_map.get(type_id).copied()
     }
 
     pub fn get_component_vec_operator(
@@ -61,9 +49,9 @@ impl Components {
     pub fn register_component<T: Component>(&mut self) -> ComponentTypeId {
         let type_id = TypeId::of::<T>();
 
-        if let None = self.type_map.get(&type_id) {
+        if self.type_map.get(&type_id).is_none() {
             let id = self.get_new_component_type_id();
-            self.type_map.insert(type_id.clone(), id.clone());
+            self.type_map.insert(type_id, id);
 
             let vec_operator = ComponentVecOperator::new::<T>();
             self.vec_operator_map.insert(id, vec_operator);
@@ -79,9 +67,15 @@ impl Components {
     }
 }
 
+impl Default for Components {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub trait Component: 'static {}
 
-type ComponentVec<T: Component> = Vec<T>;
+type ComponentVec<T> = Vec<T>;
 
 #[derive(Debug)]
 pub struct TypeErasedComponentVec {
@@ -90,13 +84,17 @@ pub struct TypeErasedComponentVec {
 
 impl TypeErasedComponentVec {
     pub fn new<T: Component>() -> Self {
+        log::trace!("Created component column for {}", std::any::type_name::<T>());
 
-        println!("Created type erased map with TypeId: {:?}", TypeId::of::<T>());
         Self {
             vec: Box::new(ComponentVec::<T>::new()),
         }
     }
 
+    pub fn capacity<T: Component>(&self) -> usize {
+        self.get::<T>().capacity()
+    }
+
     pub fn get<T: Component>(&self) -> &ComponentVec<T> {
         self.vec.downcast_ref().unwrap()
     }
@@ -131,8 +129,7 @@ impl TypeErasedComponentVec {
     }
 
     pub fn swap_remove<T: Component>(&mut self, index: usize) {
-
-        let a = self.vec
+        self.vec
             .downcast_mut::<ComponentVec<T>>()
             .unwrap()
             .swap_remove(index);
@@ -172,6 +169,8 @@ pub struct ComponentVecOperator {
     pub swap_remover: fn(&mut TypeErasedComponentVec, usize),
     pub migrator: fn(&mut TypeErasedComponentVec, &mut TypeErasedComponentVec, usize) -> (),
     pub inserter: fn(&mut TypeErasedComponentVec, Box<dyn Any>, usize) -> (),
+    pub capacity: fn(&TypeErasedComponentVec) -> usize,
+    pub item_size: usize,
 }
 
 impl ComponentVecOperator {
@@ -183,6 +182,8 @@ impl ComponentVecOperator {
             swap_remover: TypeErasedComponentVec::swap_remove::<T>,
             migrator: TypeErasedComponentVec::migrate_push::<T>,
             inserter: TypeErasedComponentVec::insert::<T>,
+            capacity: TypeErasedComponentVec::capacity::<T>,
+            item_size: std::mem::size_of::<T>(),
         }
     }
 }
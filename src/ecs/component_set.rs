@@ -4,12 +4,7 @@ use hashbrown::HashMap;
 
 use crate::ecs::entity::EntityId;
 
-use super::{
-    archetype::Archetype,
-    bitset::BitSet,
-    component::{Component, ComponentTypeId, Components, TypeErasedComponentVec},
-    world::World,
-};
+use super::component::Component;
 
 pub trait ComponentSet {
     fn get_type_id_vec() -> Vec<TypeId>;
@@ -1,6 +1,6 @@
 use super::component::Component;
 
-#[derive(Debug, Eq, Hash, PartialEq, Clone, Copy)]
+#[derive(Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Clone, Copy)]
 pub struct EntityId(u64);
 impl  Component for EntityId {}
 
@@ -31,3 +31,14 @@ impl Entities {
         EntityId(id)
     }
 }
+
+impl Default for Entities {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Marker added by World::disable, see QueryFilter
+#[derive(Debug, Clone, Copy)]
+pub struct Disabled;
+impl Component for Disabled {}
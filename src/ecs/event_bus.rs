@@ -4,8 +4,10 @@ use std::any::{Any, TypeId};
 
 pub trait WorldEvent: 'static {}
 
+type Listeners<T> = Vec<fn(&mut Storage, &T)>;
+
 pub struct WorldEventListenerList<T: WorldEvent> {
-    listeners: Vec<fn(&mut Storage, &T)>,
+    listeners: Listeners<T>,
 }
 
 impl<T: WorldEvent> WorldEventListenerList<T> {
@@ -47,13 +49,19 @@ impl EventBus {
         if let Some(list) = self.map.get_mut(&TypeId::of::<T>()) {
             let list = list.downcast_mut::<WorldEventListenerList<T>>().unwrap();
 
-            if let Some(index) = list.listeners.iter().position(|x| *x == fun) {
+            // Not guaranteed to match, the same fn can get different addresses in different codegen units
+            let index = list
+                .listeners
+                .iter()
+                .position(|listener| std::ptr::fn_addr_eq(*listener, fun));
+
+            if let Some(index) = index {
                 list.listeners.remove(index);
             }
         }
     }
 
-    pub fn get_list<T: WorldEvent + 'static>(&self) -> Option<Vec<fn(&mut Storage, &T)>> {
+    pub fn get_list<T: WorldEvent + 'static>(&self) -> Option<Listeners<T>> {
         if let Some(list) = self.map.get(&TypeId::of::<T>()) {
             let list = list.downcast_ref::<WorldEventListenerList<T>>().unwrap();
             // Clone AAAAAAAA
@@ -63,3 +71,9 @@ impl EventBus {
         None
     }
 }
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,80 @@
+use hashbrown::HashMap;
+
+use crate::math::honeycomb::{Hextor, RingLoop, SpiralLoop};
+
+use super::{component::Component, entity::EntityId};
+
+// Tile an entity is standing on. World keeps an index of these so lookups by tile
+// don't need to scan every entity. Prefer World::set_hex_position to move an entity, it updates
+// the index right away. Borrowing it mutably (query_mut, get_component_mut) makes the world
+// rebuild the whole index on the next lookup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HexPosition {
+    hex: Hextor,
+}
+
+impl Component for HexPosition {}
+
+impl HexPosition {
+    pub fn new(hex: Hextor) -> Self {
+        Self { hex }
+    }
+
+    pub fn get(&self) -> Hextor {
+        self.hex
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct HexIndex {
+    tiles: HashMap<Hextor, Vec<EntityId>>,
+}
+
+impl HexIndex {
+    pub fn new() -> Self {
+        Self {
+            tiles: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, hex: Hextor, entity_id: EntityId) {
+        self.tiles.entry(hex).or_default().push(entity_id);
+    }
+
+    pub fn remove(&mut self, hex: Hextor, entity_id: EntityId) {
+        if let Some(entities) = self.tiles.get_mut(&hex) {
+            entities.retain(|x| *x != entity_id);
+
+            if entities.is_empty() {
+                self.tiles.remove(&hex);
+            }
+        }
+    }
+
+    pub fn entities_at(&self, hex: Hextor) -> &[EntityId] {
+        self.tiles.get(&hex).map(|x| x.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn entities_in_range(&self, center: Hextor, radius: u32) -> impl Iterator<Item = EntityId> + '_ {
+        // Walking every tile of a big range costs more than checking the few occupied ones
+        let tile_count = 3 * radius as usize * (radius as usize + 1) + 1;
+        let walk_tiles = tile_count <= self.tiles.len();
+
+        let walked = walk_tiles.then(|| {
+            SpiralLoop::new(center, radius).flat_map(|hex| self.entities_at(hex).iter().copied())
+        });
+
+        let scanned = (!walk_tiles).then(|| {
+            self.tiles
+                .iter()
+                .filter(move |(hex, _)| hex.distance(&center) <= radius)
+                .flat_map(|(_, entities)| entities.iter().copied())
+        });
+
+        walked.into_iter().flatten().chain(scanned.into_iter().flatten())
+    }
+
+    pub fn entities_in_ring(&self, center: Hextor, radius: u32) -> impl Iterator<Item = EntityId> + '_ {
+        RingLoop::new(center, radius).flat_map(|hex| self.entities_at(hex).iter().copied())
+    }
+}
@@ -0,0 +1,12 @@
+use super::{component::Component, entity::EntityId};
+
+// There is no transform propagation yet, these only remember who spawned whom
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parent(pub EntityId);
+
+impl Component for Parent {}
+
+#[derive(Debug, Clone, Default)]
+pub struct Children(pub Vec<EntityId>);
+
+impl Component for Children {}
@@ -1,9 +1,13 @@
 #[macro_use]
 pub mod  world;
 pub mod singletons;
-pub mod bitset;
+pub(crate) mod bitset;
 pub mod component;
-pub mod archetype;
+pub(crate) mod archetype;
 pub mod entity;
 pub mod event_bus;
-pub mod component_set;
\ No newline at end of file
+pub mod component_set;
+pub mod name;
+pub mod hierarchy;
+pub mod hex_index;
+pub mod stats;
\ No newline at end of file
@@ -0,0 +1,36 @@
+use std::fmt::{Debug, Display};
+
+use super::component::Component;
+
+// Human readable label for an entity. World keeps an index of these so
+// entities like the camera can be found without guessing with query_single
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Name {
+    name: String,
+}
+
+impl Component for Name {}
+
+impl Name {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Debug for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Name({:?})", self.name)
+    }
+}
+
+impl Display for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
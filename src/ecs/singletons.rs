@@ -69,6 +69,12 @@ impl Singletons {
     }
 }
 
+impl Default for Singletons {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // impl<'a, T: Any, V: Any> TypeSet<'a> for (T, V) {
 //     type Output = (&'a T, &'a V);
 //     type OutputMut = (&'a mut T, &'a mut V);
@@ -147,15 +153,9 @@ macro_rules! impl_type_set {
 
             fn get_mut(singletons: &'a mut Singletons) -> Option<Self::OutputMut> {
 
-                let a = singletons.map.get_many_mut([$(&TypeId::of::<$ty>(),)+]);
-
-                if a.is_none() {
-                    return None;
-                }
-
                 let [
                     $($name,)+
-                ] = a.unwrap();
+                ] = singletons.map.get_many_mut([$(&TypeId::of::<$ty>(),)+])?;
 
                 let b = (
                     $(
@@ -0,0 +1,90 @@
+use std::fmt::Display;
+
+use super::world::World;
+
+#[derive(Debug, Clone)]
+pub struct ArchetypeStats {
+    pub component_names: Vec<String>,
+    pub entity_count: usize,
+    // Allocated capacity of every column times component size, so it is an estimate
+    pub estimated_bytes: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WorldStats {
+    pub archetypes: Vec<ArchetypeStats>,
+    pub empty_archetype_count: usize,
+    pub total_entities: usize,
+    pub estimated_bytes: usize,
+}
+
+impl World {
+    pub fn stats(&self) -> WorldStats {
+        let mut stats = WorldStats::default();
+
+        for (bitset, archetype) in self.archetype_id_map.iter() {
+            let component_names = self
+                .get_column_names(bitset)
+                .into_iter()
+                .map(|name| name.rsplit("::").next().unwrap_or(name).to_string())
+                .collect();
+
+            let mut estimated_bytes = 0;
+
+            for component_id in archetype.component_id_column_index_map.keys() {
+                let operator = self
+                    .components
+                    .get_component_vec_operator(component_id)
+                    .unwrap();
+
+                let column = archetype.get_column(component_id);
+                estimated_bytes += (operator.capacity)(column) * operator.item_size;
+            }
+
+            if archetype.len() == 0 {
+                stats.empty_archetype_count += 1;
+            }
+
+            stats.total_entities += archetype.len();
+            stats.estimated_bytes += estimated_bytes;
+
+            stats.archetypes.push(ArchetypeStats {
+                component_names,
+                entity_count: archetype.len(),
+                estimated_bytes,
+            });
+        }
+
+        // HashMap order is random, biggest archetypes first is easier to read
+        stats
+            .archetypes
+            .sort_by_key(|archetype| std::cmp::Reverse(archetype.entity_count));
+
+        stats
+    }
+}
+
+impl Display for ArchetypeStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} entities, ~{} bytes: [{}]",
+            self.entity_count,
+            self.estimated_bytes,
+            self.component_names.join(", ")
+        )
+    }
+}
+
+impl Display for WorldStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} entities in {} archetypes ({} empty), ~{} bytes",
+            self.total_entities,
+            self.archetypes.len(),
+            self.empty_archetype_count,
+            self.estimated_bytes
+        )
+    }
+}
@@ -1,48 +1,89 @@
 extern crate proc_macro;
-use core::arch;
-use hashbrown::{HashMap, HashSet};
+use hashbrown::HashMap;
 use itertools::izip;
 use std::any::{Any, TypeId};
-use std::collections::BTreeMap;
+use std::cell::{Cell, Ref, RefCell};
 use std::fmt::Debug;
 use std::hash::Hash;
-use std::iter;
-use std::marker::PhantomData;
-use std::path::Iter;
-use std::sync::Arc;
 
-use super::archetype::{self, Archetype};
-use super::bitset::{self, BitSet};
+use super::archetype::Archetype;
+use super::bitset::BitSet;
 use super::component::{
-    self, Component, ComponentTypeId, ComponentVecOperator, Components, TypeErasedComponentVec,
+    Component, ComponentTypeId, Components, TypeErasedComponentVec,
 };
 use super::component_set::ComponentSet;
-use super::entity::{self, Entities, EntityId};
-use super::event_bus::{EventBus, WorldEvent};
-use super::singletons::{self, Singletons};
+use super::entity::{Disabled, Entities, EntityId};
+use super::hex_index::{HexIndex, HexPosition};
+use super::hierarchy::{Children, Parent};
+use super::name::Name;
+use crate::math::honeycomb::Hextor;
+
+#[derive(Debug)]
+pub enum QuerySingleError {
+    NoMatch { query: &'static str },
+    MultipleMatches { query: &'static str, count: usize },
+}
+
+impl std::fmt::Display for QuerySingleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuerySingleError::NoMatch { query } => {
+                write!(f, "Expected exactly one entity for {query} but found none")
+            }
+            QuerySingleError::MultipleMatches { query, count } => {
+                write!(f, "Expected exactly one entity for {query} but found {count}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for QuerySingleError {}
 
 pub struct World {
     pub components: Components,
     pub entities: Entities,
     pub archetype_id_map: HashMap<BitSet, Archetype>,
     entity_archetype_map: HashMap<EntityId, BitSet>,
+    // Every entity with the name, oldest first. RefCell so lookups can rebuild it after Name was
+    // borrowed mutably, same as the hex index
+    name_index: RefCell<HashMap<String, Vec<EntityId>>>,
+    name_index_dirty: Cell<bool>,
+    // RefCell so lookups can rebuild it after HexPosition was borrowed mutably
+    hex_index: RefCell<HexIndex>,
+    hex_index_dirty: Cell<bool>,
 
     entity_id_id: ComponentTypeId, // LOL WHAT A NAME
+    name_id: ComponentTypeId,
+    disabled_id: ComponentTypeId,
+    hex_position_id: ComponentTypeId,
 }
 
 impl World {
     pub fn new() -> Self {
         let mut components = Components::new();
         let entity_id_id = components.register_component::<EntityId>();
+        let name_id = components.register_component::<Name>();
+        let disabled_id = components.register_component::<Disabled>();
+        let hex_position_id = components.register_component::<HexPosition>();
 
         let mut result = Self {
             entities: Entities::new(),
             archetype_id_map: HashMap::new(),
             entity_archetype_map: HashMap::new(),
+            name_index: RefCell::new(HashMap::new()),
+            name_index_dirty: Cell::new(false),
+            hex_index: RefCell::new(HexIndex::new()),
+            hex_index_dirty: Cell::new(false),
             components,
             entity_id_id,
+            name_id,
+            disabled_id,
+            hex_position_id,
         };
 
+        result.register_component::<Parent>();
+        result.register_component::<Children>();
+
         result
     }
 
@@ -66,10 +107,242 @@ impl World {
         type_names
     }
 
+    // The newest entity if several share the name
+    pub fn find_by_name(&self, name: &str) -> Option<EntityId> {
+        self.name_index().get(name)?.last().copied()
+    }
+
+    pub fn find_all_by_name(&self, name: &str) -> Vec<EntityId> {
+        self.name_index().get(name).cloned().unwrap_or_default()
+    }
+
+    pub fn set_name(&mut self, entity_id: EntityId, name: &str) {
+        self.add_component(entity_id, Name::new(name));
+    }
+
+    pub fn get_name(&self, entity_id: EntityId) -> Option<&Name> {
+        self.get_component::<Name>(entity_id)
+    }
+
+    /// Returns something like `road_placer (EntityId(4)): [EntityId, Name, Sprite]`.
+    /// Entities without a `Name` only print their id.
+    pub fn describe_entity(&self, entity_id: EntityId) -> Option<String> {
+        let bitset = self.entity_archetype_map.get(&entity_id)?;
+
+        let column_names = self
+            .get_column_names(bitset)
+            .into_iter()
+            .map(|name| name.rsplit("::").next().unwrap_or(name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let description = match self.get_name(entity_id) {
+            Some(name) => format!("{name} ({entity_id:?}): [{column_names}]"),
+            None => format!("{entity_id:?}: [{column_names}]"),
+        };
+
+        Some(description)
+    }
+
+    fn get_entity_row(&self, entity_id: EntityId) -> Option<(BitSet, usize)> {
+        let bitset = *self.entity_archetype_map.get(&entity_id)?;
+        let archetype = self.archetype_id_map.get(&bitset)?;
+
+        let row = archetype
+            .get_column(&self.entity_id_id)
+            .get::<EntityId>()
+            .iter()
+            .position(|x| *x == entity_id)?;
+
+        Some((bitset, row))
+    }
+
+    pub fn get_component<T: Component>(&self, entity_id: EntityId) -> Option<&T> {
+        let component_id = self.components.get_component_id(&TypeId::of::<T>())?;
+        let (bitset, row) = self.get_entity_row(entity_id)?;
+
+        if !bitset.contains_id(component_id.0) {
+            return None;
+        }
+
+        let archetype = self.archetype_id_map.get(&bitset)?;
+        archetype.get_column(&component_id).get::<T>().get(row)
+    }
+
+    pub fn get_component_mut<T: Component>(&mut self, entity_id: EntityId) -> Option<&mut T> {
+        self.mark_indices_dirty::<T>();
+
+        let component_id = self.components.get_component_id(&TypeId::of::<T>())?;
+        let (bitset, row) = self.get_entity_row(entity_id)?;
+
+        if !bitset.contains_id(component_id.0) {
+            return None;
+        }
+
+        let archetype = self.archetype_id_map.get_mut(&bitset)?;
+        archetype
+            .get_column_mut(&component_id)
+            .get_mut::<T>()
+            .get_mut(row)
+    }
+
+    fn index_name(&mut self, name: &Name, entity_id: EntityId) {
+        let entities = self
+            .name_index
+            .get_mut()
+            .entry(name.as_str().to_string())
+            .or_default();
+
+        if let Some(old_entity) = entities.last() {
+            log::warn!(
+                "Entity name {:?} is used by {:?} too, find_by_name gives {:?} now",
+                name.as_str(),
+                old_entity,
+                entity_id
+            );
+        }
+
+        entities.push(entity_id);
+    }
+
+    fn unindex_name(&mut self, entity_id: EntityId) {
+        let name = match self.get_name(entity_id) {
+            Some(name) => name.as_str().to_string(),
+            None => return,
+        };
+
+        let name_index = self.name_index.get_mut();
+
+        if let Some(entities) = name_index.get_mut(&name) {
+            entities.retain(|id| *id != entity_id);
+
+            if entities.is_empty() {
+                name_index.remove(&name);
+            }
+        }
+    }
+
+    // Keeps name and hex indices in sync, call before the component is pushed into a column
+    fn index_component(&mut self, entity_id: EntityId, component: &dyn Any) {
+        if let Some(name) = component.downcast_ref::<Name>() {
+            self.index_name(name, entity_id);
+        }
+
+        if let Some(hex_position) = component.downcast_ref::<HexPosition>() {
+            self.hex_index.get_mut().insert(hex_position.get(), entity_id);
+        }
+    }
+
+    // Call while the component is still in its column
+    fn unindex_component(&mut self, entity_id: EntityId, component_id: ComponentTypeId) {
+        if component_id == self.name_id {
+            self.unindex_name(entity_id);
+        }
+
+        if component_id == self.hex_position_id {
+            if let Some(hex_position) = self.get_component::<HexPosition>(entity_id).copied() {
+                self.hex_index.get_mut().remove(hex_position.get(), entity_id);
+            }
+        }
+    }
+
+    fn unindex_entity(&mut self, entity_id: EntityId) {
+        self.unindex_component(entity_id, self.name_id);
+        self.unindex_component(entity_id, self.hex_position_id);
+    }
+
+    // Name and HexPosition can be changed by anyone holding a &mut to them, the indices can't see that.
+    // So handing one out marks the index stale and the next lookup rebuilds it.
+    fn mark_indices_dirty<T: Component>(&self) {
+        if TypeId::of::<T>() == TypeId::of::<Name>() {
+            self.name_index_dirty.set(true);
+        }
+
+        if TypeId::of::<T>() == TypeId::of::<HexPosition>() {
+            self.hex_index_dirty.set(true);
+        }
+    }
+
+    fn name_index(&self) -> Ref<'_, HashMap<String, Vec<EntityId>>> {
+        if self.name_index_dirty.replace(false) {
+            let mut name_index: HashMap<String, Vec<EntityId>> = HashMap::new();
+
+            for (entity_id, name) in
+                self.query_filtered::<(EntityId, Name)>(QueryFilter::IncludeDisabled)
+            {
+                name_index
+                    .entry(name.as_str().to_string())
+                    .or_default()
+                    .push(*entity_id);
+            }
+
+            // Entity ids only grow, so sorting keeps the oldest first
+            for entities in name_index.values_mut() {
+                entities.sort();
+            }
+
+            *self.name_index.borrow_mut() = name_index;
+        }
+
+        self.name_index.borrow()
+    }
+
+    fn hex_index(&self) -> Ref<'_, HexIndex> {
+        if self.hex_index_dirty.replace(false) {
+            let mut hex_index = HexIndex::new();
+
+            for (entity_id, hex_position) in
+                self.query_filtered::<(EntityId, HexPosition)>(QueryFilter::IncludeDisabled)
+            {
+                hex_index.insert(hex_position.get(), *entity_id);
+            }
+
+            *self.hex_index.borrow_mut() = hex_index;
+        }
+
+        self.hex_index.borrow()
+    }
+
+    pub fn entities_at(&self, hex: Hextor) -> impl Iterator<Item = EntityId> + '_ {
+        let entities = self.hex_index().entities_at(hex).to_vec();
+
+        entities
+            .into_iter()
+            .filter(|entity_id| !self.is_disabled(*entity_id))
+    }
+
+    pub fn entities_in_range(&self, center: Hextor, radius: u32) -> impl Iterator<Item = EntityId> + '_ {
+        let entities: Vec<EntityId> = self.hex_index().entities_in_range(center, radius).collect();
+
+        entities
+            .into_iter()
+            .filter(|entity_id| !self.is_disabled(*entity_id))
+    }
+
+    pub fn entities_in_ring(&self, center: Hextor, radius: u32) -> impl Iterator<Item = EntityId> + '_ {
+        let entities: Vec<EntityId> = self.hex_index().entities_in_ring(center, radius).collect();
+
+        entities
+            .into_iter()
+            .filter(|entity_id| !self.is_disabled(*entity_id))
+    }
+
+    pub fn set_hex_position(&mut self, entity_id: EntityId, hex: Hextor) {
+        self.add_component(entity_id, HexPosition::new(hex));
+    }
+
     pub fn insert_entity<T: ComponentSet + 'static>(&mut self, component_set: T) -> EntityId {
+        self.insert_component_map(component_set.get_map())
+    }
+
+    // Same as insert_entity but for when component types are only known at runtime, like prefabs.
+    // Every component in the map must be registered.
+    pub fn insert_component_map(&mut self, component_map: HashMap<TypeId, Box<dyn Any>>) -> EntityId {
         let entity_id = self.get_new_entity_id();
 
-        let type_ids = T::get_type_id_vec();
+        let mut type_ids = vec![TypeId::of::<EntityId>()];
+        type_ids.extend(component_map.keys().copied());
+
         let bitset = self.get_bit_set_id(&type_ids); // I am converting type ids to component ids 3 times in this method
 
         if !self.archetype_id_map.contains_key(&bitset) {
@@ -83,13 +356,17 @@ impl World {
             self.archetype_id_map.insert(bitset, new_archetype);
         }
 
+        for component in component_map.values() {
+            self.index_component(entity_id, component.as_ref());
+        }
+
         let archetype = self.archetype_id_map.get_mut(&bitset).unwrap();
 
         // Todo: Using Box<dyn Any> to pass data
         // Not so good idea
         // but it will work for now
         // one way is to add get_as_any inside component trait
-        for (type_id, component) in component_set.get_map() {
+        for (type_id, component) in component_map {
             let id = self.components.get_component_id(&type_id).unwrap();
             let type_erased_vec = archetype.get_column_mut(&id);
             let operator = self.components.get_component_vec_operator(&id).unwrap();
@@ -102,7 +379,7 @@ impl World {
             .unwrap();
         let type_erased_vec = archetype.get_column_mut(&id);
         let operator = self.components.get_component_vec_operator(&id).unwrap();
-        (operator.pusher)(type_erased_vec, Box::new(entity_id.clone()));
+        (operator.pusher)(type_erased_vec, Box::new(entity_id));
 
         archetype.len += 1;
 
@@ -111,8 +388,10 @@ impl World {
     }
 
     pub fn remove_entity(&mut self, entity_id: EntityId) {
-        if let Some(id) = self.entity_archetype_map.get(&entity_id) {
-            let archetype = self.archetype_id_map.get_mut(id).unwrap();
+        self.unindex_entity(entity_id);
+
+        if let Some(id) = self.entity_archetype_map.remove(&entity_id) {
+            let archetype = self.archetype_id_map.get_mut(&id).unwrap();
 
             let entity_type_id = self.entity_id_id;
 
@@ -122,10 +401,10 @@ impl World {
 
             let mut change = Vec::new();
 
-            for (component_id, index) in &archetype.component_id_column_index_map {
+            for component_id in archetype.component_id_column_index_map.keys() {
                 let operator = self
                     .components
-                    .get_component_vec_operator(&component_id)
+                    .get_component_vec_operator(component_id)
                     .unwrap();
                 
                 change.push((operator, *component_id));
@@ -143,6 +422,8 @@ impl World {
     }
 
     pub fn remove_component(&mut self, entity_id: EntityId, target_id: ComponentTypeId) {
+        self.unindex_component(entity_id, target_id);
+
         // Todo: use if let Some(id) =
         let src_id = *self.entity_archetype_map.get(&entity_id).unwrap();
 
@@ -157,7 +438,7 @@ impl World {
         let mut type_ids = Vec::new();
 
         for i in 0..255 {
-            if(src_id.contains_id(i))
+            if src_id.contains_id(i)
             {
                 type_ids.push(ComponentTypeId(i));
             }
@@ -170,13 +451,11 @@ impl World {
         let mut dest_id = BitSet::new();
 
         for type_id in &type_ids {
-            dest_id.insert_id(target_id.0);
+            dest_id.insert_id(type_id.0);
         }
 
-        drop(src_archetype);
-
         if !self.archetype_id_map.contains_key(&dest_id) {
-            let mut archetype = self.create_archetype_from_type_ids(&type_ids);
+            let archetype = self.create_archetype_from_type_ids(&type_ids);
 
             self.archetype_id_map.insert(dest_id, archetype);
         };
@@ -189,12 +468,96 @@ impl World {
             .get_many_mut([&src_id, &dest_id])
             .unwrap();
 
+        // target is not migrated so it has to be dropped from src or the columns go out of sync
+        let operator = self
+            .components
+            .get_component_vec_operator(&target_id)
+            .unwrap();
+        (operator.swap_remover)(src_archetype.get_column_mut(&target_id), row);
+
         // update stuff
         self.entity_archetype_map.insert(entity_id, dest_id);
         src_archetype.len -= 1;
         dest_archetype.len += 1;
     }
 
+    // Moves the entity into the archetype with one more component. Replaces the component if
+    // the entity already has it.
+    pub fn add_component<T: Component>(&mut self, entity_id: EntityId, component: T) {
+        let target_id = self
+            .components
+            .get_component_id(&TypeId::of::<T>())
+            .expect("Component must be registered before adding it");
+
+        let Some((src_id, row)) = self.get_entity_row(entity_id) else {
+            return;
+        };
+
+        self.unindex_component(entity_id, target_id);
+        self.index_component(entity_id, &component);
+
+        if let Some(existing) = self.get_component_mut::<T>(entity_id) {
+            *existing = component;
+            return;
+        }
+
+        let mut type_ids = Vec::new();
+
+        for i in 0..255 {
+            if src_id.contains_id(i) {
+                type_ids.push(ComponentTypeId(i));
+            }
+        }
+
+        let mut dest_id = src_id;
+        dest_id.insert_id(target_id.0);
+
+        if !self.archetype_id_map.contains_key(&dest_id) {
+            let mut dest_type_ids = type_ids.clone();
+            dest_type_ids.push(target_id);
+
+            let archetype = self.create_archetype_from_type_ids(&dest_type_ids);
+            self.archetype_id_map.insert(dest_id, archetype);
+        }
+
+        self.migrate_components(&type_ids, src_id, dest_id, row);
+
+        let [src_archetype, dest_archetype] = self
+            .archetype_id_map
+            .get_many_mut([&src_id, &dest_id])
+            .unwrap();
+
+        let operator = self
+            .components
+            .get_component_vec_operator(&target_id)
+            .unwrap();
+        (operator.pusher)(dest_archetype.get_column_mut(&target_id), Box::new(component));
+
+        self.entity_archetype_map.insert(entity_id, dest_id);
+        src_archetype.len -= 1;
+        dest_archetype.len += 1;
+    }
+
+    /// Hides the entity from every query until `enable` is called.
+    /// Components and the entity id stay as they are.
+    pub fn disable(&mut self, entity_id: EntityId) {
+        if !self.is_disabled(entity_id) {
+            self.add_component(entity_id, Disabled);
+        }
+    }
+
+    pub fn enable(&mut self, entity_id: EntityId) {
+        if self.is_disabled(entity_id) {
+            self.remove_component(entity_id, self.disabled_id);
+        }
+    }
+
+    pub fn is_disabled(&self, entity_id: EntityId) -> bool {
+        self.entity_archetype_map
+            .get(&entity_id)
+            .is_some_and(|bitset| bitset.contains_id(self.disabled_id.0))
+    }
+
     fn migrate_components(
         &mut self,
         type_ids: &Vec<ComponentTypeId>,
@@ -224,7 +587,7 @@ impl World {
         let mut bitset = BitSet::new();
 
         for type_id in type_ids {
-            bitset.insert_id(self.components.get_component_id(&type_id).unwrap().0);
+            bitset.insert_id(self.components.get_component_id(type_id).unwrap().0);
         }
 
         bitset
@@ -236,33 +599,95 @@ impl World {
         for component_type_id in type_ids {
             let operator = self
                 .components
-                .get_component_vec_operator(&component_type_id)
+                .get_component_vec_operator(component_type_id)
                 .unwrap();
 
-            archetype.insert_column(component_type_id.clone(), (operator.creator)());
+            archetype.insert_column(*component_type_id, (operator.creator)());
         }
 
         archetype
     }
 
-    #[inline(always)]
-    pub fn query_single<'a, Q: Query<'a>>(&'a self) -> Q::IterType {
-        Q::query(self).next().unwrap()
+    pub fn query_single<'a, Q: Query<'a>>(&'a self) -> Result<Q::IterType, QuerySingleError> {
+        Self::take_single::<Q, _>(Q::query(self, QueryFilter::EnabledOnly))
     }
 
     #[inline(always)]
     pub fn query<'a, Q: Query<'a>>(&'a self) -> impl Iterator<Item = Q::IterType> {
-        Q::query(self)
+        Q::query(self, QueryFilter::EnabledOnly)
     }
 
     #[inline(always)]
-    pub fn query_mut_single<'a, Q: Query<'a>>(&'a mut self) -> Q::IterMutType {
-        Q::query_mut(self).next().unwrap() // Todo: Custom single query might have better performance than this
+    pub fn query_filtered<'a, Q: Query<'a>>(
+        &'a self,
+        filter: QueryFilter,
+    ) -> impl Iterator<Item = Q::IterType> {
+        Q::query(self, filter)
+    }
+
+    pub fn query_mut_single<'a, Q: Query<'a>>(
+        &'a mut self,
+    ) -> Result<Q::IterMutType, QuerySingleError> {
+        // Todo: Custom single query might have better performance than this
+        Self::take_single::<Q, _>(Q::query_mut(self, QueryFilter::EnabledOnly))
+    }
+
+    fn take_single<'a, Q: Query<'a>, T>(
+        mut iter: impl Iterator<Item = T>,
+    ) -> Result<T, QuerySingleError> {
+        let query = std::any::type_name::<Q>();
+
+        let first = iter.next().ok_or(QuerySingleError::NoMatch { query })?;
+
+        if iter.next().is_some() {
+            let count = 2 + iter.count();
+            return Err(QuerySingleError::MultipleMatches { query, count });
+        }
+
+        Ok(first)
     }
 
     #[inline(always)]
     pub fn query_mut<'a, Q: Query<'a>>(&'a mut self) -> impl Iterator<Item = Q::IterMutType> {
-        Q::query_mut(self)
+        Q::query_mut(self, QueryFilter::EnabledOnly)
+    }
+
+    #[inline(always)]
+    pub fn query_mut_filtered<'a, Q: Query<'a>>(
+        &'a mut self,
+        filter: QueryFilter,
+    ) -> impl Iterator<Item = Q::IterMutType> {
+        Q::query_mut(self, filter)
+    }
+
+    pub fn disabled_id(&self) -> ComponentTypeId {
+        self.disabled_id
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Disabled entities are skipped by every query unless asked for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QueryFilter {
+    #[default]
+    EnabledOnly,
+    IncludeDisabled,
+    DisabledOnly,
+}
+
+impl QueryFilter {
+    #[inline(always)]
+    pub fn matches(&self, bitset: &BitSet, disabled_id: ComponentTypeId) -> bool {
+        match self {
+            QueryFilter::EnabledOnly => !bitset.contains_id(disabled_id.0),
+            QueryFilter::IncludeDisabled => true,
+            QueryFilter::DisabledOnly => bitset.contains_id(disabled_id.0),
+        }
     }
 }
 
@@ -302,8 +727,8 @@ pub trait Query<'a> {
     fn get_bit_set_id(world: &'a World) -> BitSet;
     fn get_component_id(world: &'a World) -> Vec<ComponentTypeId>;
 
-    fn query(world: &'a World) -> impl Iterator<Item = Self::IterType>;
-    fn query_mut(world: &'a mut World) -> impl Iterator<Item = Self::IterMutType>;
+    fn query(world: &'a World, filter: QueryFilter) -> impl Iterator<Item = Self::IterType>;
+    fn query_mut(world: &'a mut World, filter: QueryFilter) -> impl Iterator<Item = Self::IterMutType>;
 }
 
 impl<'a, T: Component> Query<'a> for (T,) {
@@ -331,7 +756,7 @@ impl<'a, T: Component> Query<'a> for (T,) {
         vec![id]
     }
 
-    fn query(world: &'a World) -> impl Iterator<Item = Self::IterType> {
+    fn query(world: &'a World, filter: QueryFilter) -> impl Iterator<Item = Self::IterType> {
         let mut bitset = BitSet::new();
 
         bitset.insert_id(
@@ -343,12 +768,15 @@ impl<'a, T: Component> Query<'a> for (T,) {
         );
 
         let target_bitset = bitset;
+        let disabled_id = world.disabled_id;
 
         let a = world
             .archetype_id_map
             .iter()
-            .filter(move |&(bitset, _)| bitset.contains(&target_bitset))
-            .map(|(bitset, archetype)| {
+            .filter(move |&(bitset, _)| {
+                bitset.contains(&target_bitset) && filter.matches(bitset, disabled_id)
+            })
+            .map(|(_, archetype)| {
 
                 let vec_of_vecs = archetype.get_many_columns(&vec![
                     world
@@ -365,7 +793,9 @@ impl<'a, T: Component> Query<'a> for (T,) {
         a.flatten()
     }
 
-    fn query_mut(world: &'a mut World) -> impl Iterator<Item = Self::IterMutType> {
+    fn query_mut(world: &'a mut World, filter: QueryFilter) -> impl Iterator<Item = Self::IterMutType> {
+        world.mark_indices_dirty::<T>();
+
         let mut bitset = BitSet::new();
 
         bitset.insert_id(
@@ -377,11 +807,14 @@ impl<'a, T: Component> Query<'a> for (T,) {
         );
 
         let target_bitset = bitset;
+        let disabled_id = world.disabled_id;
 
         let a = world
             .archetype_id_map
             .iter_mut()
-            .filter(move |&(bitset, _)| bitset.contains(&target_bitset))
+            .filter(move |&(bitset, _)| {
+                bitset.contains(&target_bitset) && filter.matches(bitset, disabled_id)
+            })
             .map(|(_, archetype)| {
                 
                 let vec_of_vecs = archetype.get_many_columns_mut(&vec![
@@ -428,7 +861,7 @@ macro_rules! impl_query {
                 ]
             }
 
-            fn query(world: &'a World) -> impl Iterator<Item = Self::IterType> {
+            fn query(world: &'a World, filter: QueryFilter) -> impl Iterator<Item = Self::IterType> {
 
                 let mut bitset = BitSet::new();
 
@@ -441,12 +874,15 @@ macro_rules! impl_query {
                 );)+
 
                 let target_bitset = bitset;
+                let disabled_id = world.disabled_id;
 
                 let a = world
                     .archetype_id_map
                     .iter()
-                    .filter(move |&(bitset, _)| bitset.contains(&target_bitset))
-                    .map(|(bitset, archetype)| {
+                    .filter(move |&(bitset, _)| {
+                        bitset.contains(&target_bitset) && filter.matches(bitset, disabled_id)
+                    })
+                    .map(|(_, archetype)| {
 
                         let vec_of_vecs = archetype.get_many_columns(&vec![
                             $(world.components.get_component_id(&TypeId::of::<$t>()).unwrap(),)+
@@ -462,7 +898,8 @@ macro_rules! impl_query {
                 a.flatten()
             }
 
-            fn query_mut(world: &'a mut World) -> impl Iterator<Item = Self::IterMutType> {
+            fn query_mut(world: &'a mut World, filter: QueryFilter) -> impl Iterator<Item = Self::IterMutType> {
+                $(world.mark_indices_dirty::<$t>();)+
 
                 let mut bitset = BitSet::new();
 
@@ -475,11 +912,14 @@ macro_rules! impl_query {
                 );)+
 
                 let target_bitset = bitset;
+                let disabled_id = world.disabled_id;
 
                 let a = world
                     .archetype_id_map
                     .iter_mut()
-                    .filter(move |&(bitset, _)| bitset.contains(&target_bitset))
+                    .filter(move |&(bitset, _)| {
+                        bitset.contains(&target_bitset) && filter.matches(bitset, disabled_id)
+                    })
                     .map(|(_, archetype)| {
 
                         let vec_of_vecs = archetype.get_many_columns_mut(&vec![
@@ -511,3 +951,253 @@ impl_query!(6, A, B, C, D, E, F,);
 impl_query!(7, A, B, C, D, E, F, G,);
 impl_query!(8, A, B, C, D, E, F, G, H,);
 impl_query!(9, A, B, C, D, E, F, G, H, I,);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_by_name_finds_named_entities() {
+        let mut world = World::new();
+        let camera = world.insert_entity((Name::new("camera"),));
+
+        assert_eq!(world.find_by_name("camera"), Some(camera));
+        assert_eq!(world.find_by_name("player"), None);
+
+        world.remove_entity(camera);
+        assert_eq!(world.find_by_name("camera"), None);
+    }
+
+    #[test]
+    fn renaming_through_get_component_mut_updates_the_index() {
+        let mut world = World::new();
+        let entity = world.insert_entity((Name::new("old"),));
+
+        *world.get_component_mut::<Name>(entity).unwrap() = Name::new("new");
+
+        assert_eq!(world.find_by_name("old"), None);
+        assert_eq!(world.find_by_name("new"), Some(entity));
+
+        world.set_name(entity, "newer");
+
+        assert_eq!(world.find_by_name("new"), None);
+        assert_eq!(world.find_by_name("newer"), Some(entity));
+    }
+
+    #[test]
+    fn renaming_through_query_mut_updates_the_index() {
+        let mut world = World::new();
+        let entity = world.insert_entity((Name::new("old"),));
+
+        for (name,) in world.query_mut::<(Name,)>() {
+            *name = Name::new("new");
+        }
+
+        assert_eq!(world.find_by_name("old"), None);
+        assert_eq!(world.find_by_name("new"), Some(entity));
+    }
+
+    #[test]
+    fn duplicate_names_keep_the_older_entity() {
+        let mut world = World::new();
+        let first = world.insert_entity((Name::new("tree"),));
+        let second = world.insert_entity((Name::new("tree"),));
+
+        assert_eq!(world.find_by_name("tree"), Some(second));
+        assert_eq!(world.find_all_by_name("tree"), vec![first, second]);
+
+        world.remove_entity(second);
+        assert_eq!(world.find_by_name("tree"), Some(first));
+    }
+
+    #[test]
+    fn query_single_needs_exactly_one_match() {
+        let mut world = World::new();
+
+        assert!(matches!(
+            world.query_single::<(Name,)>(),
+            Err(QuerySingleError::NoMatch { .. })
+        ));
+
+        world.insert_entity((Name::new("a"),));
+        assert_eq!(world.query_single::<(Name,)>().unwrap().0.as_str(), "a");
+
+        world.insert_entity((Name::new("b"),));
+        world.insert_entity((Name::new("c"),));
+
+        assert!(matches!(
+            world.query_single::<(Name,)>(),
+            Err(QuerySingleError::MultipleMatches { count: 3, .. })
+        ));
+        assert!(matches!(
+            world.query_mut_single::<(Name,)>(),
+            Err(QuerySingleError::MultipleMatches { count: 3, .. })
+        ));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Health(u32);
+
+    impl Component for Health {}
+
+    fn names<'a>(iter: impl Iterator<Item = (&'a Name,)>) -> Vec<String> {
+        let mut names: Vec<String> = iter.map(|(name,)| name.to_string()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn disabled_entities_are_hidden_from_default_queries() {
+        let mut world = World::new();
+        world.register_component::<Health>();
+
+        world.insert_entity((Name::new("a"), Health(1)));
+        let b = world.insert_entity((Name::new("b"), Health(2)));
+
+        world.disable(b);
+
+        assert!(world.is_disabled(b));
+        assert_eq!(names(world.query::<(Name,)>()), vec!["a"]);
+        assert_eq!(world.query_mut::<(Name, Health)>().count(), 1);
+        assert!(world.query_single::<(Name,)>().is_ok());
+    }
+
+    #[test]
+    fn query_filter_finds_disabled_entities() {
+        let mut world = World::new();
+        world.insert_entity((Name::new("a"),));
+        let b = world.insert_entity((Name::new("b"),));
+
+        world.disable(b);
+        // Twice is the same as once
+        world.disable(b);
+
+        let all = world.query_filtered::<(Name,)>(QueryFilter::IncludeDisabled);
+        assert_eq!(names(all), vec!["a", "b"]);
+
+        let disabled = world.query_filtered::<(Name,)>(QueryFilter::DisabledOnly);
+        assert_eq!(names(disabled), vec!["b"]);
+    }
+
+    #[test]
+    fn enable_restores_the_entity_with_its_components() {
+        let mut world = World::new();
+        world.register_component::<Health>();
+
+        let a = world.insert_entity((Name::new("a"), Health(1)));
+        let b = world.insert_entity((Name::new("b"), Health(2)));
+
+        world.disable(a);
+        world.enable(a);
+
+        assert!(!world.is_disabled(a));
+        assert_eq!(names(world.query::<(Name,)>()), vec!["a", "b"]);
+        assert_eq!(world.query_filtered::<(Name,)>(QueryFilter::DisabledOnly).count(), 0);
+
+        // Moving between archetypes keeps every component with its entity
+        assert_eq!(world.get_component::<Health>(a), Some(&Health(1)));
+        assert_eq!(world.get_component::<Health>(b), Some(&Health(2)));
+        assert_eq!(world.find_by_name("a"), Some(a));
+    }
+
+    fn sorted(iter: impl Iterator<Item = EntityId>) -> Vec<EntityId> {
+        let mut entities: Vec<EntityId> = iter.collect();
+        entities.sort();
+        entities
+    }
+
+    fn insert_at(world: &mut World, q: i32, r: i32) -> EntityId {
+        world.insert_entity((HexPosition::new(Hextor::new(q, r)),))
+    }
+
+    #[test]
+    fn entities_at_finds_everything_on_the_tile() {
+        let mut world = World::new();
+        let a = insert_at(&mut world, 0, 0);
+        let b = insert_at(&mut world, 0, 0);
+        insert_at(&mut world, 1, 0);
+
+        assert_eq!(sorted(world.entities_at(Hextor::new(0, 0))), vec![a, b]);
+        assert_eq!(world.entities_at(Hextor::new(5, 5)).count(), 0);
+
+        world.remove_entity(a);
+        assert_eq!(sorted(world.entities_at(Hextor::new(0, 0))), vec![b]);
+    }
+
+    #[test]
+    fn entities_in_range_and_ring() {
+        let mut world = World::new();
+        let center = insert_at(&mut world, 0, 0);
+        let near = insert_at(&mut world, 1, -1);
+        let far = insert_at(&mut world, 2, 0);
+        insert_at(&mut world, 5, 0);
+
+        let origin = Hextor::new(0, 0);
+        assert_eq!(sorted(world.entities_in_range(origin, 1)), vec![center, near]);
+        assert_eq!(sorted(world.entities_in_range(origin, 2)), vec![center, near, far]);
+        assert_eq!(sorted(world.entities_in_ring(origin, 2)), vec![far]);
+    }
+
+    #[test]
+    fn entities_in_range_walks_the_tiles_of_a_crowded_index() {
+        let mut world = World::new();
+
+        // More occupied tiles than a radius 1 range has, so the range walks its tiles
+        let inside: Vec<EntityId> = (0..2).map(|q| insert_at(&mut world, q, 0)).collect();
+        for q in 2..12 {
+            insert_at(&mut world, q, 0);
+        }
+
+        assert_eq!(sorted(world.entities_in_range(Hextor::new(0, 0), 1)), inside);
+    }
+
+    #[test]
+    fn disabled_entities_are_not_found_on_tiles() {
+        let mut world = World::new();
+        let a = insert_at(&mut world, 0, 0);
+        let b = insert_at(&mut world, 0, 0);
+
+        world.disable(a);
+
+        assert_eq!(sorted(world.entities_at(Hextor::new(0, 0))), vec![b]);
+        assert_eq!(sorted(world.entities_in_range(Hextor::new(0, 0), 1)), vec![b]);
+    }
+
+    #[test]
+    fn set_hex_position_moves_the_entity_between_tiles() {
+        let mut world = World::new();
+        let entity = insert_at(&mut world, 0, 0);
+
+        world.set_hex_position(entity, Hextor::new(3, 0));
+
+        assert_eq!(world.entities_at(Hextor::new(0, 0)).count(), 0);
+        assert_eq!(sorted(world.entities_at(Hextor::new(3, 0))), vec![entity]);
+    }
+
+    #[test]
+    fn moving_through_get_component_mut_rebuilds_the_index() {
+        let mut world = World::new();
+        let entity = insert_at(&mut world, 0, 0);
+        // Builds the index before the move
+        assert_eq!(world.entities_at(Hextor::new(0, 0)).count(), 1);
+
+        *world.get_component_mut::<HexPosition>(entity).unwrap() = HexPosition::new(Hextor::new(2, 1));
+
+        assert_eq!(world.entities_at(Hextor::new(0, 0)).count(), 0);
+        assert_eq!(sorted(world.entities_at(Hextor::new(2, 1))), vec![entity]);
+        assert_eq!(sorted(world.entities_in_ring(Hextor::new(0, 0), 3)), vec![entity]);
+    }
+
+    #[test]
+    fn moving_through_query_mut_rebuilds_the_index() {
+        let mut world = World::new();
+        let entity = insert_at(&mut world, 0, 0);
+
+        for (hex_position,) in world.query_mut::<(HexPosition,)>() {
+            *hex_position = HexPosition::new(Hextor::new(-1, 0));
+        }
+
+        assert_eq!(world.entities_at(Hextor::new(0, 0)).count(), 0);
+        assert_eq!(sorted(world.entities_at(Hextor::new(-1, 0))), vec![entity]);
+    }
+}
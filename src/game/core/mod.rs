@@ -1 +0,0 @@
-pub mod level_manager;
\ No newline at end of file
@@ -0,0 +1,28 @@
+
+pub mod app;
+pub mod ecs;
+pub mod math;
+pub mod plugins;
+pub mod state;
+pub mod storage;
+
+// The things almost every game needs, `use pixel_rs::prelude::*;`
+pub mod prelude {
+    pub use crate::app::{App, AppExit, Plugin, PluginInfo, SystemStage};
+    pub use crate::ecs::{
+        component::Component,
+        entity::EntityId,
+        event_bus::WorldEvent,
+        name::Name,
+        world::{QueryFilter, World},
+    };
+    pub use crate::math::{color::Color, honeycomb::Hextor, transform2d::Transform2d};
+    pub use crate::plugins::core::{
+        asset_storage::{AssetRef, AssetStorage},
+        headless::HeadlessPlugins,
+        timer_plugin::Time,
+        CorePlugins, LogicPlugins,
+    };
+    pub use crate::state::{NextState, State};
+    pub use crate::storage::Storage;
+}
@@ -27,14 +27,14 @@ impl Color {
     }
 }
 
-impl Into<[f32; 3]> for Color {
-    fn into(self) -> [f32; 3] {
-        [self.r, self.g, self.b]
+impl From<Color> for [f32; 3] {
+    fn from(color: Color) -> Self {
+        [color.r, color.g, color.b]
     }
 }
 
-impl Into<[f32; 4]> for Color {
-    fn into(self) -> [f32; 4] {
-        [self.r, self.g, self.b, self.a]
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> Self {
+        [color.r, color.g, color.b, color.a]
     }
 }
@@ -2,9 +2,7 @@
 
 use std::ops::{Add, Mul};
 
-static SQRT_THREE: f32 = 1.7320508075688772;
-
-static SQRT_THREE_HALF: f32 = 0.8660254037844386;
+static SQRT_THREE: f32 = 1.732_050_8;
 
 pub static DIRECTION_VECTORS: [Hextor; 6] = [
     Hextor::new(1, 0),
@@ -17,11 +15,11 @@ pub static DIRECTION_VECTORS: [Hextor; 6] = [
 
 pub static HEXAGON: [[f32; 2]; 6] = [
     [0.0, 1.0],
-    [0.866025404, 0.5],
-    [0.866025404, -0.5],
+    [0.866_025_4, 0.5],
+    [0.866_025_4, -0.5],
     [0.0, -1.0],
-    [-0.866025404, -0.5],
-    [-0.866025404, 0.5],
+    [-0.866_025_4, -0.5],
+    [-0.866_025_4, 0.5],
 ];
 
 pub static HEXAGON_INDICES: &[u16] = &[0, 1, 2, 2, 3, 4, 4, 5, 0, 0, 2, 4];
@@ -44,7 +42,7 @@ impl Hextor {
 
         let mut q = float_q.round();
         let mut r = float_r.round();
-        let mut s = float_s.round();
+        let s = float_s.round();
 
         let q_frac = (float_q - q).abs();
         let r_frac = (float_r - r).abs();
@@ -71,6 +69,14 @@ impl Hextor {
         [x, y]
     }
 
+    // Number of steps between two tiles
+    pub fn distance(&self, other: &Hextor) -> u32 {
+        let q = self.q - other.q;
+        let r = self.r - other.r;
+
+        ((q.abs() + r.abs() + (q + r).abs()) / 2) as u32
+    }
+
     pub fn rotate(&self, clock_wise: bool) -> Self {
         if clock_wise {
             Self {
@@ -174,3 +180,53 @@ impl Iterator for SpiralLoop {
         Some(result)
     }
 }
+
+// Only the tiles exactly `radius` steps away from center. Radius 0 gives just the center.
+pub struct RingLoop {
+    pub center_pos: Hextor,
+    pub radius: u32,
+
+    current_direction: u32,
+    current_point: u32,
+}
+
+impl RingLoop {
+    pub fn new(center_pos: Hextor, radius: u32) -> Self {
+        Self {
+            center_pos,
+            radius,
+            current_direction: 0,
+            current_point: 0,
+        }
+    }
+}
+
+impl Iterator for RingLoop {
+    type Item = Hextor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current_direction == 6 {
+            return None;
+        }
+
+        if self.radius == 0 {
+            self.current_direction = 6;
+            return Some(self.center_pos);
+        }
+
+        // Same walk as SpiralLoop but for a single radius
+        let point = DIRECTION_VECTORS[self.current_direction as usize] * self.radius;
+        let dir = DIRECTION_VECTORS[((self.current_direction + 2) % 6) as usize];
+
+        let result = self.center_pos + point + dir * self.current_point;
+
+        self.current_point += 1;
+
+        if self.current_point == self.radius {
+            self.current_point = 0;
+            self.current_direction += 1;
+        }
+
+        Some(result)
+    }
+}
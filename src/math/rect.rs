@@ -1,12 +1,18 @@
-use glam::Vec2;
+use glam::{Mat3, Vec2};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct Rect {
     pub size: Vec2,
     pub position: Vec2,
 }
 
 impl Rect {
+    // The whole texture in uv space
+    pub const UNIT: Rect = Rect {
+        size: Vec2::ONE,
+        position: Vec2::ZERO,
+    };
+
     pub fn new(size: Vec2, position: Vec2) -> Self {
         Self { size, position }
     }
@@ -18,6 +24,25 @@ impl Rect {
         }
     }
 
+    pub fn from_min_max(min: Vec2, max: Vec2) -> Self {
+        Self {
+            size: max - min,
+            position: min,
+        }
+    }
+
+    // Smallest rect around all points, panics on an empty iterator
+    pub fn from_points(points: impl IntoIterator<Item = Vec2>) -> Self {
+        let mut points = points.into_iter();
+        let first = points.next().expect("Rect from no points");
+
+        let (min, max) = points.fold((first, first), |(min, max), point| {
+            (min.min(point), max.max(point))
+        });
+
+        Self::from_min_max(min, max)
+    }
+
     pub fn from_center(size: Vec2, center: Vec2) -> Self {
         Self {
             size,
@@ -36,4 +61,114 @@ impl Rect {
     pub fn map_uniform_position(&self, input: Vec2) -> Vec2 {
         self.position + self.size * input
     }
+
+    pub fn get_corners(&self) -> [Vec2; 4] {
+        let end = self.get_end();
+
+        [
+            self.position,
+            Vec2::new(end.x, self.position.y),
+            end,
+            Vec2::new(self.position.x, end.y),
+        ]
+    }
+
+    // Touching edges count as intersecting
+    pub fn intersects(&self, other: &Rect) -> bool {
+        let end = self.get_end();
+        let other_end = other.get_end();
+
+        self.position.x <= other_end.x
+            && other.position.x <= end.x
+            && self.position.y <= other_end.y
+            && other.position.y <= end.y
+    }
+
+    // Axis aligned bounds of the rect after transforming it, rotation makes it bigger
+    pub fn transformed(&self, matrix: &Mat3) -> Rect {
+        Rect::from_points(
+            self.get_corners()
+                .map(|corner| matrix.transform_point2(corner)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn assert_rect_eq(a: Rect, b: Rect) {
+        assert!(
+            a.position.abs_diff_eq(b.position, 1e-4) && a.size.abs_diff_eq(b.size, 1e-4),
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn touching_rects_intersect() {
+        let a = Rect::from_numbers(0.0, 0.0, 10.0, 10.0);
+
+        assert!(a.intersects(&Rect::from_numbers(10.0, 0.0, 5.0, 5.0)));
+        assert!(a.intersects(&Rect::from_numbers(0.0, -5.0, 5.0, 5.0)));
+        // Only the corner
+        assert!(a.intersects(&Rect::from_numbers(10.0, 10.0, 5.0, 5.0)));
+    }
+
+    #[test]
+    fn disjoint_rects_dont_intersect() {
+        let a = Rect::from_numbers(0.0, 0.0, 10.0, 10.0);
+
+        assert!(!a.intersects(&Rect::from_numbers(10.1, 0.0, 5.0, 5.0)));
+        assert!(!a.intersects(&Rect::from_numbers(0.0, -5.1, 5.0, 5.0)));
+        // Overlapping on x alone is not enough
+        assert!(!a.intersects(&Rect::from_numbers(2.0, 20.0, 5.0, 5.0)));
+    }
+
+    #[test]
+    fn contained_rect_intersects() {
+        let a = Rect::from_numbers(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::from_numbers(2.0, 2.0, 1.0, 1.0);
+
+        assert!(a.intersects(&b));
+        assert!(b.intersects(&a));
+    }
+
+    #[test]
+    fn from_points_is_the_smallest_rect_around_them() {
+        let rect = Rect::from_points([
+            Vec2::new(1.0, -2.0),
+            Vec2::new(-3.0, 4.0),
+            Vec2::new(0.0, 0.0),
+        ]);
+
+        assert_rect_eq(
+            rect,
+            Rect::from_min_max(Vec2::new(-3.0, -2.0), Vec2::new(1.0, 4.0)),
+        );
+    }
+
+    #[test]
+    fn transformed_by_a_quarter_turn_swaps_width_and_height() {
+        let rect = Rect::from_center(Vec2::new(4.0, 2.0), Vec2::ZERO);
+        let matrix = Mat3::from_angle(FRAC_PI_2);
+
+        assert_rect_eq(
+            rect.transformed(&matrix),
+            Rect::from_center(Vec2::new(2.0, 4.0), Vec2::ZERO),
+        );
+    }
+
+    #[test]
+    fn transformed_by_an_eighth_turn_grows() {
+        let rect = Rect::from_center(Vec2::splat(2.0), Vec2::ZERO);
+        let matrix = Mat3::from_angle(FRAC_PI_2 * 0.5);
+
+        let diagonal = 2.0 * std::f32::consts::SQRT_2;
+        assert_rect_eq(
+            rect.transformed(&matrix),
+            Rect::from_center(Vec2::splat(diagonal), Vec2::ZERO),
+        );
+    }
 }
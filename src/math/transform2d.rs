@@ -1,10 +1,10 @@
-use std::ops::Mul;
 
-use bytemuck::{Pod, Zeroable};
-use glam::{Mat2, Mat3, Vec2, Vec3, Vec4};
+use glam::{Mat3, Vec2, Vec3, Vec4};
 
 use crate::ecs::component::Component;
 
+use super::rect::Rect;
+
 #[derive(Debug, Default, Clone)]
 
 pub struct Transform2d {
@@ -48,6 +48,11 @@ impl Transform2d {
         }
     }
 
+    // World bounds of a size sized quad centered on this transform, like a sprite
+    pub fn bounds(&self, size: Vec2) -> Rect {
+        Rect::from_center(size, Vec2::ZERO).transformed(&self.create_matrix())
+    }
+
     pub const IDENTITY: Transform2d = Transform2d {
         position: Vec2 { x: 0.0, y: 0.0 },
         rotation: 0.0,
@@ -88,3 +93,39 @@ impl AlignedMatrix {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn bounds_are_scaled_and_moved() {
+        let transform2d = Transform2d {
+            position: Vec2::new(10.0, -5.0),
+            rotation: 0.0,
+            scale: Vec2::new(2.0, 3.0),
+        };
+
+        let bounds = transform2d.bounds(Vec2::new(4.0, 4.0));
+
+        assert!(bounds.position.abs_diff_eq(Vec2::new(6.0, -11.0), 1e-4));
+        assert!(bounds.size.abs_diff_eq(Vec2::new(8.0, 12.0), 1e-4));
+    }
+
+    #[test]
+    fn bounds_of_a_rotated_quad_are_axis_aligned() {
+        let transform2d = Transform2d {
+            position: Vec2::ZERO,
+            rotation: FRAC_PI_2,
+            scale: Vec2::new(2.0, 1.0),
+        };
+
+        // 4x1 scaled to 8x1, then stood upright
+        let bounds = transform2d.bounds(Vec2::new(4.0, 1.0));
+
+        assert!(bounds.get_center().abs_diff_eq(Vec2::ZERO, 1e-4));
+        assert!(bounds.size.abs_diff_eq(Vec2::new(1.0, 8.0), 1e-4));
+    }
+}
@@ -0,0 +1,276 @@
+use std::path::Path;
+
+use glam::{UVec2, Vec2};
+use hashbrown::HashMap;
+use log::error;
+use serde::Deserialize;
+
+use crate::{math::rect::Rect, plugins::core::asset_storage::Asset};
+
+use super::{image::Image, sprite_sheet::SpriteSheet};
+
+// TrueType fonts are rasterized once at this size, Text2d scales the quads
+const RASTER_SIZE: f32 = 48.0;
+const ATLAS_WIDTH: u32 = 512;
+const GLYPH_PADDING: u32 = 2;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    pub region: Rect, // uv rect in the atlas
+    // Everything below is in font pixels
+    pub size: Vec2,
+    pub offset: Vec2, // bottom left of the glyph from the pen position on the baseline
+    pub advance: f32,
+}
+
+// Either a .ttf/.otf file or a bitmap font file like this, glyphs are read row by row:
+//
+// image = "font.png" # relative to the font file
+// glyph_size = [8, 12]
+// chars = " !\"#$%&'()*+,-./0123456789"
+// spacing = [0, 0] # optional
+// offset = [0, 0] # optional
+// advance = 7 # optional, glyph width by default
+// line_height = 14 # optional, glyph height by default
+#[derive(Debug)]
+pub struct Font {
+    pub atlas: Image,
+    pub size: f32, // Pixel height the glyphs were made for
+    pub ascent: f32,
+    pub line_height: f32,
+    glyphs: HashMap<char, Glyph>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitmapFontFile {
+    image: String,
+    glyph_size: [u32; 2],
+    chars: String,
+    #[serde(default)]
+    spacing: [u32; 2],
+    #[serde(default)]
+    offset: [u32; 2],
+    advance: Option<f32>,
+    line_height: Option<f32>,
+}
+
+impl Asset for Font {
+    // A broken font gives one without glyphs, AssetStorage::get goes through load and reports the error
+    fn from_binary(binary: Vec<u8>) -> Self {
+        Self::load(binary, "").unwrap_or_else(|err| {
+            error!("{err}");
+            Self::new(Image::new(UVec2::ONE, vec![0; 4]), 1.0, 0.0, 0.0)
+        })
+    }
+
+    fn load(binary: Vec<u8>, path: &str) -> Result<Self, String> {
+        let is_true_type = matches!(
+            binary.get(0..4),
+            Some([0, 1, 0, 0]) | Some(b"true") | Some(b"OTTO") | Some(b"ttcf")
+        );
+
+        if is_true_type {
+            Self::from_true_type(&binary)
+        } else {
+            let text = String::from_utf8(binary)
+                .map_err(|err| format!("Bitmap font is not valid utf8: {err}"))?;
+            let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+
+            Self::from_bitmap(&text, dir)
+        }
+    }
+}
+
+impl Font {
+    // Without glyphs, for fonts put together in code
+    pub fn new(atlas: Image, size: f32, ascent: f32, line_height: f32) -> Self {
+        Self {
+            atlas,
+            size,
+            ascent,
+            line_height,
+            glyphs: HashMap::new(),
+        }
+    }
+
+    pub fn insert_glyph(&mut self, character: char, glyph: Glyph) {
+        self.glyphs.insert(character, glyph);
+    }
+
+    pub fn get_glyph(&self, character: char) -> Option<&Glyph> {
+        self.glyphs.get(&character)
+    }
+
+    // The image path is relative to dir, the folder of the font file
+    fn from_bitmap(text: &str, dir: &Path) -> Result<Self, String> {
+        let file: BitmapFontFile =
+            toml::from_str(text).map_err(|err| format!("Invalid bitmap font: {err}"))?;
+
+        let image_path = dir.join(&file.image).to_string_lossy().into_owned();
+
+        let image = image::open(&image_path)
+            .map_err(|err| format!("Bitmap font image {image_path} error: {err}"))?
+            .to_rgba8();
+
+        let image_size = UVec2::new(image.width(), image.height());
+        let glyph_size = UVec2::from(file.glyph_size);
+
+        let mut sheet = SpriteSheet::new(&image_path, image_size);
+        sheet.add_grid(
+            glyph_size,
+            None,
+            None,
+            UVec2::from(file.spacing),
+            UVec2::from(file.offset),
+        );
+
+        let size = glyph_size.as_vec2();
+        let advance = file.advance.unwrap_or(size.x);
+
+        let glyphs = file
+            .chars
+            .chars()
+            .enumerate()
+            .map(|(index, character)| {
+                let region = sheet
+                    .get_frame(index)
+                    .ok_or(format!("Bitmap font image {image_path} has no cell for {character}"))?;
+
+                let glyph = Glyph {
+                    region,
+                    size,
+                    offset: Vec2::ZERO,
+                    advance,
+                };
+
+                Ok((character, glyph))
+            })
+            .collect::<Result<_, String>>()?;
+
+        Ok(Self {
+            atlas: Image::new(image_size, image.into_raw()),
+            size: size.y,
+            ascent: size.y,
+            line_height: file.line_height.unwrap_or(size.y),
+            glyphs,
+        })
+    }
+
+    // Printable ascii and latin-1, packed into rows of the atlas
+    fn from_true_type(binary: &[u8]) -> Result<Self, String> {
+        let settings = fontdue::FontSettings {
+            scale: RASTER_SIZE,
+            ..Default::default()
+        };
+
+        let font = fontdue::Font::from_bytes(binary, settings)
+            .map_err(|err| format!("Invalid font: {err}"))?;
+
+        let line_metrics = font
+            .horizontal_line_metrics(RASTER_SIZE)
+            .ok_or("Font has no horizontal line metrics")?;
+
+        let characters = (' '..='~').chain('\u{a0}'..='\u{ff}');
+
+        let mut rasterized = Vec::new();
+        let mut cursor = UVec2::ZERO;
+        let mut row_height = 0;
+
+        for character in characters {
+            if !font.has_glyph(character) {
+                continue;
+            }
+
+            let (metrics, coverage) = font.rasterize(character, RASTER_SIZE);
+            let size = UVec2::new(metrics.width as u32, metrics.height as u32);
+
+            if cursor.x + size.x + GLYPH_PADDING > ATLAS_WIDTH {
+                cursor = UVec2::new(0, cursor.y + row_height + GLYPH_PADDING);
+                row_height = 0;
+            }
+
+            rasterized.push((character, metrics, coverage, cursor));
+
+            cursor.x += size.x + GLYPH_PADDING;
+            row_height = row_height.max(size.y);
+        }
+
+        let atlas_size = UVec2::new(ATLAS_WIDTH, (cursor.y + row_height).max(1));
+        let mut data = vec![0; (atlas_size.x * atlas_size.y * 4) as usize];
+
+        let mut glyphs = HashMap::new();
+
+        for (character, metrics, coverage, position) in rasterized {
+            // White pixels with the coverage as alpha, Text2d color tints them
+            for (index, alpha) in coverage.iter().enumerate() {
+                let x = position.x + (index % metrics.width) as u32;
+                let y = position.y + (index / metrics.width) as u32;
+                let pixel = ((y * atlas_size.x + x) * 4) as usize;
+
+                data[pixel..pixel + 4].copy_from_slice(&[255, 255, 255, *alpha]);
+            }
+
+            let size = Vec2::new(metrics.width as f32, metrics.height as f32);
+
+            let glyph = Glyph {
+                region: Rect::new(size / atlas_size.as_vec2(), position.as_vec2() / atlas_size.as_vec2()),
+                size,
+                offset: Vec2::new(metrics.xmin as f32, metrics.ymin as f32),
+                advance: metrics.advance_width,
+            };
+
+            glyphs.insert(character, glyph);
+        }
+
+        Ok(Self {
+            atlas: Image::new(atlas_size, data),
+            size: RASTER_SIZE,
+            ascent: line_metrics.ascent,
+            line_height: line_metrics.new_line_size,
+            glyphs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    const FONT: &str = "image = \"font.png\"\nglyph_size = [8, 8]\nchars = \"ab\"\n";
+
+    #[test]
+    fn broken_fonts_are_errors() {
+        assert!(Font::load(vec![0xff, 0xfe], "font.toml").is_err());
+        assert!(Font::load(b"glyph_size = 8".to_vec(), "font.toml").is_err());
+        assert!(Font::load(b"true garbage".to_vec(), "font.ttf").is_err());
+        // No font.png next to it
+        assert!(Font::load(FONT.as_bytes().to_vec(), "missing/font.toml").is_err());
+    }
+
+    #[test]
+    fn bitmap_image_is_relative_to_the_font_file() {
+        let dir = std::env::temp_dir().join(format!("pixel-rs-font-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // Room for two 8x8 glyphs
+        Image::new(UVec2::new(16, 8), vec![255; 16 * 8 * 4])
+            .save_png(dir.join("font.png"))
+            .unwrap();
+
+        let font_path = dir.join("font.toml");
+        let font = Font::load(FONT.as_bytes().to_vec(), font_path.to_str().unwrap());
+
+        // A third character has no cell in the image
+        let too_many_chars = FONT.replace("\"ab\"", "\"abc\"");
+        let missing_cell = Font::load(too_many_chars.into_bytes(), font_path.to_str().unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        let font = font.unwrap();
+        assert_eq!(font.atlas.get_size(), UVec2::new(16, 8));
+        assert!(font.get_glyph('b').is_some());
+        assert!(missing_cell.is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use glam::UVec2;
 use image::GenericImageView;
 
@@ -30,4 +32,19 @@ impl Image {
     pub fn get_data(&self) -> &Vec<u8> {
         &self.data
     }
+
+    pub fn get_size(&self) -> UVec2 {
+        self.size
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> image::ImageResult<()> {
+        image::save_buffer_with_format(
+            path,
+            &self.data,
+            self.size.x,
+            self.size.y,
+            image::ColorType::Rgba8,
+            image::ImageFormat::Png,
+        )
+    }
 }
@@ -1 +1,9 @@
-pub mod image;
\ No newline at end of file
+#[cfg(feature = "text")]
+pub mod font;
+#[cfg(feature = "png")]
+pub mod image;
+pub mod prefab;
+#[cfg(feature = "render")]
+pub mod shader;
+#[cfg(feature = "png")]
+pub mod sprite_sheet;
\ No newline at end of file
@@ -0,0 +1,482 @@
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use glam::Vec2;
+use hashbrown::HashMap;
+use log::{error, warn};
+use serde::Deserialize;
+
+use crate::{
+    app::{Plugin, PluginInfo},
+    ecs::{
+        component::Component,
+        entity::EntityId,
+        hierarchy::{Children, Parent},
+        name::Name,
+        world::World,
+    },
+    math::{color::Color, transform2d::Transform2d},
+    plugins::core::asset_storage::{Asset, AssetRef, AssetStorage, AssetStoragePlugin},
+    storage::Storage,
+};
+
+// A prefab file looks like this. Every table under `components` is looked up by
+// the component type name in the PrefabRegistry, missing fields use the defaults
+// of that component.
+//
+// [components.Transform2d]
+// scale = [1.0, 1.0]
+//
+// [components.Sprite]
+// image = "image.png" # relative paths start at the folder of the prefab file
+// size = [64.0, 18.0]
+//
+// [[children]]
+// [children.components.Name]
+// name = "child"
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PrefabNode {
+    #[serde(default)]
+    pub components: toml::Table,
+    #[serde(default)]
+    pub children: Vec<PrefabNode>,
+}
+
+#[derive(Debug, Default)]
+pub struct Prefab {
+    // Rc so spawning doesn't have to copy the whole tree out of the asset storage
+    pub root: Rc<PrefabNode>,
+    pub dir: PathBuf,
+}
+
+impl Asset for Prefab {
+    // A broken prefab gives an empty one, AssetStorage::get goes through load and reports the error
+    fn from_binary(binary: Vec<u8>) -> Self {
+        Self::load(binary, "").unwrap_or_else(|err| {
+            error!("{err}");
+            Self::default()
+        })
+    }
+
+    fn load(binary: Vec<u8>, path: &str) -> Result<Self, String> {
+        let text = String::from_utf8(binary).map_err(|err| format!("Prefab is not valid utf8: {err}"))?;
+        let root = toml::from_str(&text).map_err(|err| format!("Invalid prefab: {err}"))?;
+        let dir = Path::new(path).parent().unwrap_or(Path::new("")).to_path_buf();
+
+        Ok(Self {
+            root: Rc::new(root),
+            dir,
+        })
+    }
+}
+
+pub struct PrefabFields<'a> {
+    component: &'a str,
+    table: &'a toml::Table,
+    dir: &'a Path,
+}
+
+impl<'a> PrefabFields<'a> {
+    pub fn new(component: &'a str, table: &'a toml::Table, dir: &'a Path) -> Self {
+        Self {
+            component,
+            table,
+            dir,
+        }
+    }
+
+    fn get_as<T>(&self, field: &str, convert: impl Fn(&toml::Value) -> Option<T>) -> Option<T> {
+        let value = self.table.get(field)?;
+        let result = convert(value);
+
+        if result.is_none() {
+            warn!(
+                "Prefab field {}.{} has unexpected value {}",
+                self.component, field, value
+            );
+        }
+
+        result
+    }
+
+    pub fn get_f32(&self, field: &str) -> Option<f32> {
+        self.get_as(field, value_to_f32)
+    }
+
+    pub fn get_i32(&self, field: &str) -> Option<i32> {
+        self.get_as(field, |value| value.as_integer().map(|x| x as i32))
+    }
+
+    pub fn get_bool(&self, field: &str) -> Option<bool> {
+        self.get_as(field, |value| value.as_bool())
+    }
+
+    pub fn get_str(&self, field: &str) -> Option<&'a str> {
+        self.table.get(field).and_then(|value| value.as_str())
+    }
+
+    // For files, relative paths are turned into paths relative to the prefab file
+    pub fn get_path(&self, field: &str) -> Option<String> {
+        let path = self.get_str(field)?;
+
+        Some(self.dir.join(path).to_string_lossy().into_owned())
+    }
+
+    pub fn get_vec2(&self, field: &str) -> Option<Vec2> {
+        self.get_as(field, |value| match value.as_array()?.as_slice() {
+            [x, y] => Some(Vec2::new(value_to_f32(x)?, value_to_f32(y)?)),
+            _ => None,
+        })
+    }
+
+    pub fn get_color(&self, field: &str) -> Option<Color> {
+        self.get_as(field, |value| {
+            let values = value
+                .as_array()?
+                .iter()
+                .map(value_to_f32)
+                .collect::<Option<Vec<f32>>>()?;
+
+            match values.as_slice() {
+                [r, g, b] => Some(Color::from_rgb(*r, *g, *b)),
+                [r, g, b, a] => Some(Color::from_rgba(*r, *g, *b, *a)),
+                _ => None,
+            }
+        })
+    }
+}
+
+fn value_to_f32(value: &toml::Value) -> Option<f32> {
+    match value {
+        toml::Value::Float(x) => Some(*x as f32),
+        toml::Value::Integer(x) => Some(*x as f32),
+        _ => None,
+    }
+}
+
+// Err when the fields can't make a component, the entity is spawned without it
+pub trait FromPrefab: Component + Sized {
+    fn from_prefab(fields: &PrefabFields, assets: &mut AssetStorage) -> Result<Self, String>;
+}
+
+pub type BuildComponentFn = fn(&PrefabFields, &mut AssetStorage) -> Result<Box<dyn Any>, String>;
+
+pub struct PrefabBuilder {
+    pub type_id: TypeId,
+    pub build: BuildComponentFn,
+}
+
+fn build_component<T: FromPrefab>(
+    fields: &PrefabFields,
+    assets: &mut AssetStorage,
+) -> Result<Box<dyn Any>, String> {
+    Ok(Box::new(T::from_prefab(fields, assets)?))
+}
+
+#[derive(Default)]
+pub struct PrefabRegistry {
+    builders: HashMap<String, PrefabBuilder>,
+}
+
+impl PrefabRegistry {
+    pub fn new() -> Self {
+        Self {
+            builders: HashMap::new(),
+        }
+    }
+
+    // Prefabs refer to components by their type name without the module path
+    pub fn register<T: FromPrefab>(&mut self) {
+        let full_name = std::any::type_name::<T>();
+        let name = full_name.rsplit("::").next().unwrap_or(full_name);
+
+        self.builders.insert(
+            name.to_string(),
+            PrefabBuilder {
+                type_id: TypeId::of::<T>(),
+                build: build_component::<T>,
+            },
+        );
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PrefabBuilder> {
+        self.builders.get(name)
+    }
+}
+
+// Overrides are applied to the root entity of the prefab only.
+// Field overrides are merged into the prefab table before the component is built,
+// component overrides replace (or add) the whole component.
+#[derive(Default)]
+pub struct PrefabOverrides {
+    fields: HashMap<String, toml::Table>,
+    // Type name kept for the error when the component isn't registered
+    components: HashMap<TypeId, (&'static str, Box<dyn Any>)>,
+}
+
+impl PrefabOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(mut self, component: &str, field: &str, value: impl Into<toml::Value>) -> Self {
+        self.fields
+            .entry(component.to_string())
+            .or_default()
+            .insert(field.to_string(), value.into());
+
+        self
+    }
+
+    // T has to be registered in the world, otherwise it is skipped with an error
+    pub fn with<T: Component>(mut self, component: T) -> Self {
+        self.components.insert(
+            TypeId::of::<T>(),
+            (std::any::type_name::<T>(), Box::new(component)),
+        );
+
+        self
+    }
+}
+
+pub struct PrefabPlugin;
+
+impl Plugin for PrefabPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![PluginInfo::of::<AssetStoragePlugin>()]
+    }
+
+    fn build(app: &mut crate::app::App) {
+        app.storage.singletons.insert(PrefabRegistry::new());
+
+        app.storage.register_prefab_component::<Name>();
+        app.storage.register_prefab_component::<Transform2d>();
+    }
+}
+
+impl Storage {
+    // Needs PrefabPlugin
+    pub fn register_prefab_component<T: FromPrefab>(&mut self) {
+        self.world.register_component::<T>();
+        self.singletons
+            .get_mut::<PrefabRegistry>()
+            .unwrap()
+            .register::<T>();
+    }
+
+    pub fn spawn_prefab(&mut self, prefab: &AssetRef<Prefab>, overrides: PrefabOverrides) -> EntityId {
+        let (registry, assets) = self
+            .singletons
+            .get_many_mut::<(PrefabRegistry, AssetStorage)>()
+            .unwrap();
+
+        // The builders need the asset storage mutably to load images
+        let prefab = assets.get_data(prefab);
+        let root = Rc::clone(&prefab.root);
+        let dir = prefab.dir.clone();
+
+        spawn_prefab_node(&mut self.world, registry, assets, &root, &dir, overrides, None)
+    }
+}
+
+fn spawn_prefab_node(
+    world: &mut World,
+    registry: &PrefabRegistry,
+    assets: &mut AssetStorage,
+    node: &PrefabNode,
+    dir: &Path,
+    overrides: PrefabOverrides,
+    parent: Option<EntityId>,
+) -> EntityId {
+    let mut component_map: HashMap<TypeId, Box<dyn Any>> = HashMap::new();
+
+    for name in overrides.fields.keys() {
+        if !node.components.contains_key(name) {
+            warn!("Prefab has no {name} component, its field overrides are ignored");
+        }
+    }
+
+    for (name, value) in node.components.iter() {
+        let Some(builder) = registry.get(name) else {
+            warn!("Prefab component {name} is not registered, skipping it");
+            continue;
+        };
+
+        let empty = toml::Table::new();
+        let mut table = Cow::Borrowed(value.as_table().unwrap_or(&empty));
+
+        if let Some(override_fields) = overrides.fields.get(name) {
+            table.to_mut().extend(override_fields.clone());
+        }
+
+        match (builder.build)(&PrefabFields::new(name, &table, dir), assets) {
+            Ok(component) => {
+                component_map.insert(builder.type_id, component);
+            }
+            Err(err) => error!("Prefab component {name}: {err}, skipping it"),
+        }
+    }
+
+    for (type_id, (name, component)) in overrides.components {
+        if world.components.get_component_id(&type_id).is_none() {
+            error!("Prefab override component {name} is not registered, skipping it");
+            continue;
+        }
+
+        component_map.insert(type_id, component);
+    }
+
+    if let Some(parent) = parent {
+        component_map.insert(TypeId::of::<Parent>(), Box::new(Parent(parent)));
+    }
+
+    if !node.children.is_empty() {
+        component_map.insert(TypeId::of::<Children>(), Box::new(Children::default()));
+    }
+
+    let entity_id = world.insert_component_map(component_map);
+
+    for child in node.children.iter() {
+        let child_id = spawn_prefab_node(
+            world,
+            registry,
+            assets,
+            child,
+            dir,
+            PrefabOverrides::new(),
+            Some(entity_id),
+        );
+
+        if let Some(children) = world.get_component_mut::<Children>(entity_id) {
+            children.0.push(child_id);
+        }
+    }
+
+    entity_id
+}
+
+impl FromPrefab for Transform2d {
+    fn from_prefab(fields: &PrefabFields, _: &mut AssetStorage) -> Result<Self, String> {
+        Ok(Self::new(
+            fields.get_vec2("position").unwrap_or(Vec2::ZERO),
+            fields.get_f32("rotation").unwrap_or(0.0),
+            fields.get_vec2("scale").unwrap_or(Vec2::ONE),
+        ))
+    }
+}
+
+impl FromPrefab for Name {
+    fn from_prefab(fields: &PrefabFields, _: &mut AssetStorage) -> Result<Self, String> {
+        Ok(Name::new(fields.get_str("name").unwrap_or_default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREFAB: &str = r#"
+[components.Name]
+name = "road"
+
+[components.Transform2d]
+position = [1.0, 2.0]
+scale = [2.0, 2.0]
+
+[[children]]
+[children.components.Name]
+name = "road_child"
+"#;
+
+    struct Unregistered;
+
+    impl Component for Unregistered {}
+
+    fn create_storage() -> (Storage, AssetRef<Prefab>) {
+        let mut storage = Storage::new();
+        storage.singletons.insert(PrefabRegistry::new());
+        storage.singletons.insert(AssetStorage::new());
+
+        storage.register_prefab_component::<Name>();
+        storage.register_prefab_component::<Transform2d>();
+
+        let prefab = Prefab::load(PREFAB.as_bytes().to_vec(), "prefabs/road.prefab.toml").unwrap();
+        let assets = storage.singletons.get_mut::<AssetStorage>().unwrap();
+        assets.insert(prefab, "road");
+        let prefab = assets.get::<Prefab>("road").unwrap();
+
+        (storage, prefab)
+    }
+
+    #[test]
+    fn broken_prefabs_are_errors() {
+        assert!(Prefab::load(vec![0xff, 0xfe], "road.prefab.toml").is_err());
+        assert!(Prefab::load(b"[components".to_vec(), "road.prefab.toml").is_err());
+    }
+
+    #[test]
+    fn paths_are_relative_to_the_prefab_file() {
+        let prefab = Prefab::load(PREFAB.as_bytes().to_vec(), "prefabs/road.prefab.toml").unwrap();
+        let table = toml::Table::from_iter([("image".to_string(), "road.png".into())]);
+        let fields = PrefabFields::new("Sprite", &table, &prefab.dir);
+
+        assert_eq!(
+            fields.get_path("image"),
+            Some(Path::new("prefabs").join("road.png").to_string_lossy().into_owned())
+        );
+    }
+
+    #[test]
+    fn spawns_the_components_and_children() {
+        let (mut storage, prefab) = create_storage();
+        let root = storage.spawn_prefab(&prefab, PrefabOverrides::new());
+
+        let world = &storage.world;
+        assert_eq!(world.find_by_name("road"), Some(root));
+        assert_eq!(
+            world.get_component::<Transform2d>(root).unwrap().position,
+            Vec2::new(1.0, 2.0)
+        );
+
+        let child = world.find_by_name("road_child").unwrap();
+        assert_eq!(world.get_component::<Children>(root).unwrap().0, vec![child]);
+        assert_eq!(world.get_component::<Parent>(child).unwrap().0, root);
+    }
+
+    #[test]
+    fn overrides_apply_to_the_root() {
+        let (mut storage, prefab) = create_storage();
+
+        let overrides = PrefabOverrides::new()
+            .set("Transform2d", "position", toml::Value::Array(vec![5.into(), 6.into()]))
+            .with(Name::new("overridden"));
+        let root = storage.spawn_prefab(&prefab, overrides);
+
+        let world = &storage.world;
+        assert_eq!(world.find_by_name("overridden"), Some(root));
+        assert!(world.find_by_name("road_child").is_some());
+
+        let transform = world.get_component::<Transform2d>(root).unwrap();
+        assert_eq!(transform.position, Vec2::new(5.0, 6.0));
+        // Fields that weren't overridden come from the prefab
+        assert_eq!(transform.scale, Vec2::new(2.0, 2.0));
+    }
+
+    #[test]
+    fn unregistered_and_undeclared_overrides_are_skipped() {
+        let (mut storage, prefab) = create_storage();
+
+        let overrides = PrefabOverrides::new()
+            .set("Sprite", "size", 4.0)
+            .with(Unregistered);
+        let root = storage.spawn_prefab(&prefab, overrides);
+
+        assert_eq!(storage.world.find_by_name("road"), Some(root));
+        assert!(storage.world.get_component::<Unregistered>(root).is_none());
+    }
+}
@@ -0,0 +1,91 @@
+use log::error;
+use wgpu::{naga, Device, RenderPipeline, ShaderModule};
+
+use crate::plugins::core::asset_storage::Asset;
+
+// WGSL source. Loaded through ShaderWatcher so it gets reloaded when the file changes.
+pub struct Shader {
+    pub source: String,
+}
+
+impl Asset for Shader {
+    fn from_binary(binary: Vec<u8>) -> Self {
+        Self {
+            source: String::from_utf8_lossy(&binary).into_owned(),
+        }
+    }
+}
+
+impl Shader {
+    pub fn new(source: &str) -> Self {
+        Self {
+            source: source.to_string(),
+        }
+    }
+
+    // Same checks wgpu does when creating the module, but we get the error back instead of the
+    // device panicking
+    pub fn validate(&self) -> Result<(), String> {
+        let module = naga::front::wgsl::parse_str(&self.source)
+            .map_err(|err| err.emit_to_string(&self.source))?;
+
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .map_err(|err| err.emit_to_string(&self.source))?;
+
+        Ok(())
+    }
+
+    // `create` builds the pipeline from the compiled module. None when the shader doesn't compile or
+    // the pipeline doesn't accept it, the error is logged.
+    pub fn create_pipeline(
+        &self,
+        device: &Device,
+        label: &str,
+        create: impl FnOnce(&ShaderModule) -> RenderPipeline,
+    ) -> Option<RenderPipeline> {
+        if let Err(err) = self.validate() {
+            error!("Shader {label} failed to compile:\n{err}");
+            return None;
+        }
+
+        // Catches what naga can't know about, like bindings that don't match the pipeline layout
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(label),
+            source: wgpu::ShaderSource::Wgsl(self.source.as_str().into()),
+        });
+
+        let pipeline = create(&module);
+
+        if let Some(err) = pollster::block_on(device.pop_error_scope()) {
+            error!("Pipeline for shader {label} is invalid: {err}");
+            return None;
+        }
+
+        Some(pipeline)
+    }
+
+    // For startup. `build` gets this shader first and the embedded copy of the source when the file
+    // on disk is broken, so a bad edit doesn't stop the game from starting. Reloading keeps watching
+    // the file. Panics only when the embedded source is broken too.
+    pub fn build_with_fallback<T>(
+        &self,
+        label: &str,
+        embedded_source: &str,
+        build: impl Fn(&Shader) -> Option<T>,
+    ) -> T {
+        if let Some(result) = build(self) {
+            return result;
+        }
+
+        error!("Shader {label} is broken, using the embedded source until it is fixed");
+
+        build(&Shader::new(embedded_source))
+            .unwrap_or_else(|| panic!("Embedded source of shader {label} is broken"))
+    }
+}
@@ -0,0 +1,260 @@
+use std::path::Path;
+
+use glam::UVec2;
+use hashbrown::HashMap;
+use log::error;
+use serde::Deserialize;
+
+use crate::{
+    math::rect::Rect,
+    plugins::core::asset_storage::{Asset, AssetRef, AssetStorage},
+};
+
+use super::image::Image;
+
+// Regions of one atlas image. A sheet file looks like this, both tables are optional.
+// Grid frames are numbered row by row starting at the top left.
+//
+// image = "atlas.png" # relative to the sheet file
+//
+// [grid]
+// tile_size = [32, 32]
+// columns = 4 # defaults to as many as fit in the image
+// rows = 2
+// spacing = [2, 2]
+// offset = [0, 0]
+//
+// [regions]
+// road_straight = [0, 64, 64, 18] # x, y, width, height in pixels
+#[derive(Debug)]
+pub struct SpriteSheet {
+    pub image: String,
+    pub image_size: UVec2,
+    frames: Vec<Rect>,
+    regions: HashMap<String, Rect>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SheetFile {
+    image: String,
+    grid: Option<GridFile>,
+    #[serde(default)]
+    regions: std::collections::HashMap<String, [u32; 4]>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GridFile {
+    tile_size: [u32; 2],
+    columns: Option<u32>,
+    rows: Option<u32>,
+    #[serde(default)]
+    spacing: [u32; 2],
+    #[serde(default)]
+    offset: [u32; 2],
+}
+
+impl Asset for SpriteSheet {
+    // A broken sheet gives an empty one, AssetStorage::get goes through load and reports the error
+    fn from_binary(binary: Vec<u8>) -> Self {
+        Self::load(binary, "").unwrap_or_else(|err| {
+            error!("{err}");
+            Self::new("", UVec2::ZERO)
+        })
+    }
+
+    fn load(binary: Vec<u8>, path: &str) -> Result<Self, String> {
+        let text = String::from_utf8(binary)
+            .map_err(|err| format!("Sprite sheet is not valid utf8: {err}"))?;
+        let file: SheetFile =
+            toml::from_str(&text).map_err(|err| format!("Invalid sprite sheet: {err}"))?;
+
+        // Relative to the sheet file, like the paths in prefabs
+        let dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        let image_path = dir.join(&file.image).to_string_lossy().into_owned();
+
+        // Only reads the header, the pixels are loaded through AssetStorage as usual
+        let (width, height) = image::image_dimensions(&image_path)
+            .map_err(|err| format!("Sprite sheet image {image_path} error: {err}"))?;
+
+        let mut sheet = Self::new(&image_path, UVec2::new(width, height));
+
+        if let Some(grid) = file.grid {
+            sheet.add_grid(
+                UVec2::from(grid.tile_size),
+                grid.columns,
+                grid.rows,
+                UVec2::from(grid.spacing),
+                UVec2::from(grid.offset),
+            );
+        }
+
+        for (name, [x, y, w, h]) in file.regions {
+            sheet.add_region(
+                &name,
+                Rect::from_numbers(x as f32, y as f32, w as f32, h as f32),
+            );
+        }
+
+        Ok(sheet)
+    }
+}
+
+impl SpriteSheet {
+    pub fn new(image: &str, image_size: UVec2) -> Self {
+        Self {
+            image: image.to_string(),
+            image_size,
+            frames: Vec::new(),
+            regions: HashMap::new(),
+        }
+    }
+
+    // Appends columns * rows frames, None fits as many as the image has room for
+    pub fn add_grid(
+        &mut self,
+        tile_size: UVec2,
+        columns: Option<u32>,
+        rows: Option<u32>,
+        spacing: UVec2,
+        offset: UVec2,
+    ) {
+        if tile_size.x == 0 || tile_size.y == 0 {
+            error!(
+                "Sprite sheet {} has a grid with an empty tile size",
+                self.image
+            );
+            return;
+        }
+
+        let step = tile_size + spacing;
+        // An offset past the image just fits nothing
+        let fits = (self.image_size.saturating_sub(offset) + spacing) / step;
+
+        let columns = columns.unwrap_or(fits.x);
+        let rows = rows.unwrap_or(fits.y);
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let position = offset + step * UVec2::new(column, row);
+
+                self.frames
+                    .push(Rect::new(tile_size.as_vec2(), position.as_vec2()));
+            }
+        }
+    }
+
+    // Rect in pixels
+    pub fn add_region(&mut self, name: &str, rect: Rect) {
+        self.regions.insert(name.to_string(), rect);
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    // Uv rects, ready to put in Sprite::region
+    pub fn get_frame(&self, index: usize) -> Option<Rect> {
+        self.frames.get(index).map(|rect| self.to_uv(rect))
+    }
+
+    pub fn get_region(&self, name: &str) -> Option<Rect> {
+        self.regions.get(name).map(|rect| self.to_uv(rect))
+    }
+
+    pub fn get_image(&self, assets: &mut AssetStorage) -> AssetRef<Image> {
+        assets.get::<Image>(&self.image).unwrap()
+    }
+
+    fn to_uv(&self, rect: &Rect) -> Rect {
+        let size = self.image_size.as_vec2();
+
+        Rect::new(rect.size / size, rect.position / size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    const SHEET: &str = "image = \"atlas.png\"\n[grid]\ntile_size = [16, 16]\n";
+
+    #[test]
+    fn broken_sheets_are_errors() {
+        assert!(SpriteSheet::load(vec![0xff, 0xfe], "sheet.toml").is_err());
+        assert!(SpriteSheet::load(b"image = ".to_vec(), "sheet.toml").is_err());
+        // No atlas.png next to it
+        assert!(SpriteSheet::load(SHEET.as_bytes().to_vec(), "missing/sheet.toml").is_err());
+    }
+
+    #[test]
+    fn image_is_relative_to_the_sheet_file() {
+        let dir = std::env::temp_dir().join(format!("pixel-rs-sheet-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let image_path = dir.join("atlas.png");
+        Image::new(UVec2::new(32, 16), vec![0; 32 * 16 * 4])
+            .save_png(image_path.to_str().unwrap())
+            .unwrap();
+
+        let sheet_path = dir.join("sheet.toml");
+        let sheet = SpriteSheet::load(SHEET.as_bytes().to_vec(), sheet_path.to_str().unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let sheet = sheet.unwrap();
+        assert_eq!(sheet.image, image_path.to_str().unwrap());
+        assert_eq!(sheet.image_size, UVec2::new(32, 16));
+        assert_eq!(sheet.frame_count(), 2);
+    }
+
+    #[test]
+    fn grid_fits_as_many_frames_as_the_image_has_room_for() {
+        let mut sheet = SpriteSheet::new("atlas.png", UVec2::new(64, 32));
+        sheet.add_grid(UVec2::new(16, 16), None, None, UVec2::ZERO, UVec2::ZERO);
+
+        assert_eq!(sheet.frame_count(), 8);
+        // Row by row from the top left
+        assert_eq!(
+            sheet.get_frame(5),
+            Some(Rect::from_numbers(0.25, 0.5, 0.25, 0.5))
+        );
+    }
+
+    #[test]
+    fn grid_spacing_is_only_between_frames() {
+        // 3 * 20 + 2 * 2 = 64, the last frame doesn't need spacing after it
+        let mut sheet = SpriteSheet::new("atlas.png", UVec2::new(64, 20));
+        sheet.add_grid(
+            UVec2::new(20, 20),
+            None,
+            None,
+            UVec2::new(2, 2),
+            UVec2::ZERO,
+        );
+
+        assert_eq!(sheet.frame_count(), 3);
+    }
+
+    #[test]
+    fn grid_offset_larger_than_the_image_has_no_frames() {
+        let mut sheet = SpriteSheet::new("atlas.png", UVec2::new(64, 64));
+        sheet.add_grid(
+            UVec2::new(16, 16),
+            None,
+            None,
+            UVec2::ZERO,
+            UVec2::new(80, 8),
+        );
+
+        assert_eq!(sheet.frame_count(), 0);
+    }
+
+    #[test]
+    fn grid_with_empty_tiles_is_ignored() {
+        let mut sheet = SpriteSheet::new("atlas.png", UVec2::new(64, 64));
+        sheet.add_grid(UVec2::new(0, 16), None, None, UVec2::ZERO, UVec2::ZERO);
+
+        assert_eq!(sheet.frame_count(), 0);
+    }
+}
@@ -15,6 +15,15 @@ use crate::app::Plugin;
 
 pub trait Asset {
     fn from_binary(binary: Vec<u8>) -> Self;
+
+    // What AssetStorage calls, `path` is the file the binary was read from.
+    // Assets that can fail to parse override this, the error is logged instead of panicking.
+    fn load(binary: Vec<u8>, _path: &str) -> Result<Self, String>
+    where
+        Self: Sized,
+    {
+        Ok(Self::from_binary(binary))
+    }
 }
 
 #[derive(Debug)]
@@ -69,9 +78,9 @@ impl AssetStorage {
     fn get_id(value: &str) -> u64 {
         let mut hasher = DefaultHasher::new();
         value.hash(&mut hasher);
-        let id = hasher.finish();
+        
 
-        id
+        hasher.finish()
     }
 
     pub fn insert<T: Asset + 'static>(&mut self, asset: T, name: &str) -> bool {
@@ -79,11 +88,13 @@ impl AssetStorage {
 
         if !self.data.contains_key(&id) {
             self.data.insert(id, Box::new(asset));
+            // So get() can hand out refs to it like any loaded asset
+            self.ref_counters.insert(id, Rc::new(RefCell::new(0)));
 
             return true;
         }
 
-        return false;
+        false
     }
 
     pub fn get<T: Asset + 'static>(&mut self, path: &str) -> Option<AssetRef<T>> {
@@ -91,23 +102,54 @@ impl AssetStorage {
 
         if !self.data.contains_key(&id) {
             // Todo: Set the path here
-            if let Ok(content) = fs::read(path.clone()) {
-                self.data.insert(id, Box::new(T::from_binary(content)));
-                self.ref_counters.insert(id, Rc::new(RefCell::new(0)));
+            if let Ok(content) = fs::read(path) {
+                match T::load(content, path) {
+                    Ok(asset) => {
+                        self.data.insert(id, Box::new(asset));
+                        self.ref_counters.insert(id, Rc::new(RefCell::new(0)));
+                    }
+                    Err(err) => {
+                        log::error!("Could not load asset at {path}: {err}");
+                        return None;
+                    }
+                }
             } else {
-                panic!("Asset at {} not found", path.clone())
+                panic!("Asset at {} not found", path)
             }
         }
 
         if self.data.contains_key(&id) {
             let counter_ref = self.ref_counters.get(&id).unwrap();
 
-            return Some(AssetRef::new(id, Rc::clone(counter_ref)));
+            Some(AssetRef::new(id, Rc::clone(counter_ref)))
         } else {
-            panic!("Asset at path {} id {} not present", path.clone(), id)
+            panic!("Asset at path {} id {} not present", path, id)
         }
+    }
+
+    // Reads the file again, refs to the asset see the new data. False if the file can't be read or parsed,
+    // the old data is kept then.
+    pub fn reload<T: Asset + 'static>(&mut self, path: &str) -> bool {
+        let id = Self::get_id(path);
+
+        let asset = fs::read(path)
+            .map_err(|err| err.to_string())
+            .and_then(|content| T::load(content, path));
+
+        match asset {
+            Ok(asset) => {
+                self.data.insert(id, Box::new(asset));
+                self.ref_counters
+                    .entry(id)
+                    .or_insert_with(|| Rc::new(RefCell::new(0)));
 
-        None
+                true
+            }
+            Err(err) => {
+                log::warn!("Could not reload asset at {path}: {err}");
+                false
+            }
+        }
     }
 
     pub fn get_data<T: Asset + 'static>(&self, asset: &AssetRef<T>) -> &T {
@@ -126,7 +168,7 @@ impl AssetStorage {
 
         for (id, count) in self.ref_counters.iter() {
             if *count.borrow().deref() == 0 {
-                unused_assets.push(id.clone());
+                unused_assets.push(*id);
             }
         }
 
@@ -137,6 +179,12 @@ impl AssetStorage {
     }
 }
 
+impl Default for AssetStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub struct AssetStoragePlugin;
 
 impl Plugin for AssetStoragePlugin {
@@ -0,0 +1,102 @@
+use bytemuck::Pod;
+use wgpu::{Buffer, BufferUsages, Device, Queue};
+
+// A Vec on the cpu side and a gpu buffer that grows with it.
+// Push values during PreRender, then call write_buffer once before rendering.
+pub struct BufferVec<T: Pod> {
+    values: Vec<T>,
+    buffer: Buffer,
+    capacity: usize,
+    usage: BufferUsages,
+    label: String,
+}
+
+impl<T: Pod> BufferVec<T> {
+    pub fn new(device: &Device, usage: BufferUsages, label: &str, capacity: usize) -> Self {
+        let usage = usage | BufferUsages::COPY_DST;
+        let capacity = capacity.max(1);
+
+        Self {
+            values: Vec::new(),
+            buffer: create_buffer::<T>(device, usage, label, capacity),
+            capacity,
+            usage,
+            label: label.to_string(),
+        }
+    }
+
+    pub fn push(&mut self, value: T) -> usize {
+        self.values.push(value);
+        self.values.len() - 1
+    }
+
+    pub fn extend_from_slice(&mut self, values: &[T]) {
+        self.values.extend_from_slice(values);
+    }
+
+    pub fn resize(&mut self, len: usize, value: T) {
+        self.values.resize(len, value);
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    // Uploads the values, the buffer is recreated with at least double the capacity when they don't fit.
+    // Returns true when that happened, bind groups pointing at the old buffer have to be recreated.
+    pub fn write_buffer(&mut self, device: &Device, queue: &Queue) -> bool {
+        let mut recreated = false;
+
+        if self.values.len() > self.capacity {
+            self.capacity = self.values.len().max(self.capacity * 2);
+            self.buffer = create_buffer::<T>(device, self.usage, &self.label, self.capacity);
+
+            recreated = true;
+        }
+
+        let data: &[u8] = bytemuck::cast_slice(&self.values);
+
+        // Writes have to be a multiple of 4 bytes, the buffer is already rounded up
+        if data.len().is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT as usize) {
+            queue.write_buffer(&self.buffer, 0, data);
+        } else {
+            let mut padded = data.to_vec();
+            padded.resize(data.len().next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT as usize), 0);
+            queue.write_buffer(&self.buffer, 0, &padded);
+        }
+
+        recreated
+    }
+}
+
+fn create_buffer<T: Pod>(device: &Device, usage: BufferUsages, label: &str, capacity: usize) -> Buffer {
+    let align_mask = wgpu::COPY_BUFFER_ALIGNMENT - 1;
+    let size = (capacity * std::mem::size_of::<T>()) as u64;
+
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: (size + align_mask) & !align_mask,
+        usage,
+        mapped_at_creation: false,
+    })
+}
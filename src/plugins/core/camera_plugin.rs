@@ -1,20 +1,31 @@
-use std::default;
-
-use glam::{vec3, Mat3, Vec2, Vec3};
+use crate::app::PluginInfo;
+#[cfg(feature = "render")]
+use crate::plugins::core::render_plugin::RenderPlugin;
+#[cfg(feature = "window")]
+use crate::plugins::core::window::window_plugin::WindowPlugin;
+
+use glam::{Mat3, Vec2, Vec3};
+#[cfg(feature = "render")]
 use wgpu::{util::DeviceExt, BindGroup, BindGroupLayout, Buffer};
-use winit::window::Window;
 
 use crate::{
     app::{Plugin, SystemStage},
-    ecs::{
-        singletons::{self, Singletons},
-        world::{self, World}, component::Component,
-    },
-    math::transform2d::{AlignedMatrix, Transform2d},
-    storage::{self, Storage},
+    ecs::{component::Component, name::Name},
+    math::{rect::Rect, transform2d::Transform2d},
+    storage::Storage,
+};
+
+#[cfg(feature = "render")]
+use crate::{
+    ecs::{singletons::Singletons, world::World},
+    math::transform2d::AlignedMatrix,
 };
 
-use super::render_plugin::{Gpu, Renderer};
+#[cfg(feature = "render")]
+use super::render_graph::{RenderContext, RenderNode, MAIN_PASS};
+#[cfg(feature = "render")]
+use super::render_plugin::Gpu;
+use super::window::window_events::WindowState;
 
 pub struct Camera {
     pub projection: Mat3,
@@ -22,85 +33,107 @@ pub struct Camera {
 
 impl Component for Camera {}
 
+#[cfg(feature = "render")]
 pub struct CameraBindGroup {
     pub layout: BindGroupLayout,
     pub bind_group: BindGroup,
     buffer: Buffer,
 }
 
+// Camera entity and Viewport work without a gpu, so game logic can turn the cursor into world
+// positions in headless apps too. With RenderPlugin the camera matrix is uploaded for the renderers.
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
-    fn build(app: &mut crate::app::App) {
-        let projection = Mat3::IDENTITY;
+    fn optional_dependencies() -> Vec<PluginInfo> {
+        vec![
+            #[cfg(feature = "render")]
+            PluginInfo::of::<RenderPlugin>(),
+            #[cfg(feature = "window")]
+            PluginInfo::of::<WindowPlugin>(),
+        ]
+    }
 
+    fn build(app: &mut crate::app::App) {
         app.storage.world.register_component::<Camera>();
-        app.storage.world.register_component::<Transform2d>(); //Todo: Think about this, transform should part of core
-        
+        app.storage.world.register_component::<Transform2d>();
 
         app.storage.world.insert_entity((
+            Name::new("main_camera"),
             Camera {
                 projection: Mat3::IDENTITY,
             },
             Transform2d::IDENTITY,
         ));
 
-        let gpu = app.storage.singletons.get::<Gpu>().unwrap();
-
-        let camera_bind_group_layout =
-            gpu.device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: Some("Camera bind group layout"),
-                    entries: &[wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::VERTEX,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                        count: None,
-                    }],
-                });
-
-        let camera_buffer = gpu
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Camera buffer"),
-                contents: bytemuck::cast_slice(&[AlignedMatrix::IDENTITY]),
-                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            });
-
-        let camera_bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Camera bind group"),
-            layout: &camera_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer.as_entire_binding(),
-            }],
-        });
-
-        let data = CameraBindGroup {
-            layout: camera_bind_group_layout,
-            bind_group: camera_bind_group,
-            buffer: camera_buffer,
-        };
-
         app.storage.singletons.insert(Viewport::default());
-        app.storage.singletons.insert(data);
+        // Offscreen targets never get a Resize, so set up the projection right away
+        on_resize(&mut app.storage);
         app.schedular.add_system(SystemStage::Resize, on_resize);
         app.schedular.add_system(SystemStage::PreRender, on_update);
-        app.renderers.push(Box::new(CameraPlugin));
+
+        #[cfg(feature = "render")]
+        if app.storage.singletons.get::<Gpu>().is_some() {
+            build_camera_bind_group(app);
+        }
     }
 }
 
-impl Renderer for CameraPlugin {
-    fn render<'pass, 'encoder: 'pass, 'world: 'encoder>(
-        &self,
-        render_pass: &mut wgpu::RenderPass<'encoder>,
-        world: &'world World,
-        singletons: &'world Singletons,
-    ) {
+#[cfg(feature = "render")]
+fn build_camera_bind_group(app: &mut crate::app::App) {
+    let gpu = app.storage.singletons.get::<Gpu>().unwrap();
+
+    let camera_bind_group_layout =
+        gpu.device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Camera bind group layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+
+    let camera_buffer = gpu
+        .device
+        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera buffer"),
+            contents: bytemuck::cast_slice(&[AlignedMatrix::IDENTITY]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+    let camera_bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Camera bind group"),
+        layout: &camera_bind_group_layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: camera_buffer.as_entire_binding(),
+        }],
+    });
+
+    let data = CameraBindGroup {
+        layout: camera_bind_group_layout,
+        bind_group: camera_bind_group,
+        buffer: camera_buffer,
+    };
+
+    app.storage.singletons.insert(data);
+    // Uploads the camera matrix, everything drawn in the main pass needs it
+    app.render_graph.add_node("camera", Box::new(CameraPlugin));
+    app.render_graph
+        .get_node_mut(MAIN_PASS)
+        .unwrap()
+        .after("camera");
+}
+
+#[cfg(feature = "render")]
+impl RenderNode for CameraPlugin {
+    fn run(&self, _context: &mut RenderContext, _world: &World, singletons: &Singletons) {
         let (gpu, data, viewport) = singletons
             .get_many::<(Gpu, CameraBindGroup, Viewport)>()
             .unwrap();
@@ -114,9 +147,19 @@ impl Renderer for CameraPlugin {
 }
 
 pub fn on_resize(world: &mut Storage) {
-    let size = world.singletons.get::<Window>().unwrap().inner_size();
-
-    let (camera,) = world.world.query_mut_single::<(Camera,)>();
+    let size = world
+        .singletons
+        .get::<WindowState>()
+        .copied()
+        .unwrap_or_default();
+
+    let (camera,) = match world.world.query_mut_single::<(Camera,)>() {
+        Ok(camera) => camera,
+        Err(err) => {
+            log::error!("{err}");
+            return;
+        }
+    };
 
     camera.projection.x_axis.x = 2.0 / size.width as f32;
     camera.projection.y_axis.y = 2.0 / size.height as f32;
@@ -151,19 +194,79 @@ impl Viewport {
         &self.inv_projection_view_mat
     }
 
+    // World to clip space, -1 to 1 with y going up
+    pub fn get_world_to_screen_mat(&self) -> &Mat3 {
+        &self.projection_view_mat
+    }
+
     pub fn get_size(&self) -> Vec2 {
         self.size
     }
+
+    // Camera projection times the camera transform, the inverse is kept for screen_to_world
+    pub fn set_projection_view(&mut self, projection_view_mat: Mat3) {
+        self.inv_projection_view_mat = projection_view_mat.inverse();
+        self.projection_view_mat = projection_view_mat; // but this is camera projection I want world projection hmm
+    }
+
+    // Clip space is -1 to 1, everything outside of this is off screen and can be culled
+    pub fn visible_world_rect(&self) -> Rect {
+        Rect::from_center(Vec2::splat(2.0), Vec2::ZERO).transformed(&self.inv_projection_view_mat)
+    }
 }
 
 pub fn on_update(storage: &mut Storage) {
-    let size = storage.singletons.get::<Window>().unwrap().inner_size();
-
-    let (camera, transform2d) = storage.world.query_single::<(Camera, Transform2d)>();
+    let (camera, transform2d) = match storage.world.query_single::<(Camera, Transform2d)>() {
+        Ok(camera) => camera,
+        Err(err) => {
+            log::error!("{err}");
+            return;
+        }
+    };
 
     let projection = camera.projection * transform2d.create_matrix();
 
     let viewport = storage.singletons.get_mut::<Viewport>().unwrap();
-    viewport.inv_projection_view_mat = projection.inverse();
-    viewport.projection_view_mat = projection; // but this is camera projection I want world projection hmm
+    viewport.set_projection_view(projection);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // What on_resize and on_update give for an 800x600 window with the camera at the origin
+    fn viewport_800x600() -> Viewport {
+        let mut viewport = Viewport::default();
+        viewport.set_projection_view(Mat3::from_scale(Vec2::new(2.0 / 800.0, 2.0 / 600.0)));
+        viewport
+    }
+
+    #[test]
+    fn visible_world_rect_is_the_window_in_pixels() {
+        let rect = viewport_800x600().visible_world_rect();
+
+        assert!(rect.position.abs_diff_eq(Vec2::new(-400.0, -300.0), 1e-3));
+        assert!(rect.size.abs_diff_eq(Vec2::new(800.0, 600.0), 1e-3));
+    }
+
+    #[test]
+    fn zooming_out_shows_more_of_the_world() {
+        let mut viewport = Viewport::default();
+        let projection = Mat3::from_scale(Vec2::new(2.0 / 800.0, 2.0 / 600.0));
+        viewport.set_projection_view(projection * Mat3::from_scale(Vec2::splat(0.5)));
+
+        let rect = viewport.visible_world_rect();
+
+        assert!(rect.get_center().abs_diff_eq(Vec2::ZERO, 1e-3));
+        assert!(rect.size.abs_diff_eq(Vec2::new(1600.0, 1200.0), 1e-3));
+    }
+
+    #[test]
+    fn screen_to_world_inverts_the_projection() {
+        let viewport = viewport_800x600();
+
+        let world = viewport.screen_to_world(Vec2::new(1.0, -1.0));
+
+        assert!(world.abs_diff_eq(Vec2::new(400.0, -300.0), 1e-3));
+    }
 }
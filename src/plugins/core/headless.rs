@@ -0,0 +1,133 @@
+use glam::Vec2;
+
+use crate::{
+    app::{App, Plugin, PluginInfo},
+    plugins::core::{
+        input::{
+            input_plugin::{ButtonState, Input, KeyEvent, MouseButton, MouseButtonEvent},
+            keycode::KeyCode,
+        },
+        timer_plugin::{Time, TimerPlugin},
+        user_settings::{UserSettingsPlugin, UserSettingsStore},
+        window::{window_events::WindowState, window_settings::WindowSettings},
+    },
+};
+
+use super::LogicPlugins;
+
+#[derive(Default)]
+pub struct HeadlessSettings {
+    // None runs until something emits AppExit
+    pub frames: Option<u64>,
+}
+
+// LogicPlugins with a runner that just calls App::update in a loop, no window or gpu needed.
+// User settings are defaults kept in memory unless a UserSettingsStore was inserted before,
+// so tests and simulations never read or write the settings file.
+pub struct HeadlessPlugins;
+
+impl Plugin for HeadlessPlugins {
+    fn build(app: &mut App) {
+        if app.storage.singletons.get::<UserSettingsStore>().is_none() {
+            app.storage.singletons.insert(UserSettingsStore::InMemory);
+        }
+
+        app.register_plugin::<LogicPlugins>();
+        app.register_plugin::<HeadlessRunnerPlugin>();
+    }
+}
+
+pub struct HeadlessRunnerPlugin;
+
+impl Plugin for HeadlessRunnerPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![PluginInfo::of::<TimerPlugin>()]
+    }
+
+    fn optional_dependencies() -> Vec<PluginInfo> {
+        vec![PluginInfo::of::<UserSettingsPlugin>()]
+    }
+
+    fn build(app: &mut App) {
+        app.storage.singletons.insert(HeadlessSettings::default());
+
+        // Acts like a window of the configured size, so saving the settings doesn't change the size either
+        let window_settings = app
+            .storage
+            .singletons
+            .get::<WindowSettings>()
+            .cloned()
+            .unwrap_or_default();
+
+        app.storage.singletons.insert(WindowState {
+            width: window_settings.width,
+            height: window_settings.height,
+            ..Default::default()
+        });
+
+        // 60 fps, change Time::fixed_delta_time for something else
+        let time = app.storage.singletons.get_mut::<Time>().unwrap();
+        time.fixed_delta_time = Some(1.0 / 60.0);
+
+        app.set_runner(runner);
+    }
+}
+
+fn runner(mut app: App) {
+    let frames = app
+        .storage
+        .singletons
+        .get::<HeadlessSettings>()
+        .unwrap()
+        .frames;
+
+    match frames {
+        Some(frames) => app.run_frames(frames),
+        None => {
+            app.build_plugins();
+
+            while !app.exit_requested() {
+                app.update();
+            }
+        }
+    }
+
+    app.shutdown();
+}
+
+// Synthetic input, goes through the same path as the winit events do
+impl App {
+    pub fn run_frames(&mut self, frames: u64) {
+        self.build_plugins();
+
+        for _ in 0..frames {
+            if self.exit_requested() {
+                break;
+            }
+
+            self.update();
+        }
+    }
+
+    pub fn send_key(&mut self, button: KeyCode, state: ButtonState) {
+        let input = self.storage.singletons.get_mut::<Input>().unwrap();
+        input.on_keyboard_input(KeyEvent { button, state });
+
+        self.on_keyboard_input();
+    }
+
+    pub fn send_mouse_button(&mut self, button: MouseButton, state: ButtonState) {
+        let input = self.storage.singletons.get_mut::<Input>().unwrap();
+        input.on_mouse_input(MouseButtonEvent { button, state });
+
+        self.on_mouse_input();
+    }
+
+    // Screen position in -1..1, same as the window plugin
+    pub fn send_cursor_position(&mut self, position: Vec2) {
+        let input = self.storage.singletons.get_mut::<Input>().unwrap();
+        input.on_curser_moved(position);
+
+        self.on_curser_moved();
+    }
+}
@@ -1,10 +1,8 @@
-use std::f32::consts::E;
 
 use glam::Vec2;
 use hashbrown::HashMap;
-use winit::keyboard::PhysicalKey::Code;
 
-use crate::{app::Plugin, ecs::world::World};
+use crate::app::Plugin;
 
 use super::keycode::KeyCode;
 
@@ -90,25 +88,25 @@ impl Input {
     pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
         let a = self.mouse_input.get(&button);
 
-        let result = if let Some(input) = a {
+        
+
+        if let Some(input) = a {
             input.state == ButtonState::Pressed
         } else {
             false
-        };
-
-        result
+        }
     }
 
     pub fn is_key_pressed(&self, key: KeyCode) -> bool {
         let a = self.keyboard_input.get(&key);
 
-        let result = if let Some(input) = a {
+        
+
+        if let Some(input) = a {
             input.state == ButtonState::Pressed
         } else {
             false
-        };
-
-        result
+        }
     }
 
     pub fn mouse_position(&self) -> Vec2 {
@@ -1,4 +1,6 @@
-#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum KeyCode {
     /// <kbd>`</kbd> on a US keyboard. This is also called a backtick or grave.
     /// This is the <kbd>半角</kbd>/<kbd>全角</kbd>/<kbd>漢字</kbd>
@@ -1,25 +1,66 @@
-use crate::app::Plugin;
+use crate::{app::Plugin, plugins::asset_types::prefab::PrefabPlugin};
 
 use self::{
     asset_storage::AssetStoragePlugin, input::input_plugin::InputPlugin,
-    render_plugin::RenderPlugin, timer_plugin::TimerPlugin, window::window_plugin::WindowPlugin,
+    timer_plugin::TimerPlugin, user_settings::UserSettingsPlugin,
 };
 
+#[cfg(feature = "render")]
+use self::{render_plugin::RenderPlugin, shader_reload::ShaderReloadPlugin};
+#[cfg(feature = "window")]
+use self::window::window_plugin::WindowPlugin;
+
 pub mod asset_storage;
+#[cfg(feature = "render")]
+pub mod buffer_vec;
 pub mod camera_plugin;
+pub mod headless;
 pub mod input;
+#[cfg(feature = "render")]
+pub mod render_graph;
+#[cfg(feature = "render")]
 pub mod render_plugin;
+#[cfg(feature = "render")]
+pub mod shader_reload;
 pub mod timer_plugin;
+pub mod user_settings;
 pub mod window;
 
+// Everything the game needs, window and gpu included when those features are on
 pub struct CorePlugins;
 
 impl Plugin for CorePlugins {
+    fn build(app: &mut crate::app::App) {
+        app.register_plugin::<LogicPlugins>();
+        app.register_plugin::<WindowRenderPlugins>();
+    }
+}
+
+// Works without a window or gpu
+pub struct LogicPlugins;
+
+impl Plugin for LogicPlugins {
     fn build(app: &mut crate::app::App) {
         app.register_plugin::<InputPlugin>();
-        app.register_plugin::<WindowPlugin>();
         app.register_plugin::<TimerPlugin>();
         app.register_plugin::<AssetStoragePlugin>();
+        app.register_plugin::<PrefabPlugin>();
+        app.register_plugin::<UserSettingsPlugin>();
+    }
+}
+
+pub struct WindowRenderPlugins;
+
+impl Plugin for WindowRenderPlugins {
+    // Registers nothing without the window and render features
+    #[allow(unused_variables)]
+    fn build(app: &mut crate::app::App) {
+        #[cfg(feature = "window")]
+        app.register_plugin::<WindowPlugin>();
+
+        #[cfg(feature = "render")]
         app.register_plugin::<RenderPlugin>();
+        #[cfg(feature = "render")]
+        app.register_plugin::<ShaderReloadPlugin>();
     }
 }
@@ -0,0 +1,298 @@
+use hashbrown::{HashMap, HashSet};
+use wgpu::{Buffer, CommandEncoder, TextureView};
+
+use crate::ecs::{singletons::Singletons, world::World};
+
+use super::render_plugin::Renderer;
+
+// The texture of the current frame, the window surface most of the time
+pub const SURFACE_SLOT: &str = "surface";
+
+// Sprites, tilemaps and meshes are drawn in here
+pub const MAIN_PASS: &str = "main_2d";
+
+// Named textures and buffers nodes pass to each other during one frame.
+// Everything is dropped at the end of the frame, keep long living gpu data in a singleton.
+pub struct RenderContext<'a> {
+    pub encoder: &'a mut CommandEncoder,
+    pub textures: HashMap<String, TextureView>,
+    pub buffers: HashMap<String, Buffer>,
+}
+
+impl<'a> RenderContext<'a> {
+    pub fn get_texture(&self, slot: &str) -> &TextureView {
+        self.textures
+            .get(slot)
+            .unwrap_or_else(|| panic!("Render graph texture slot {slot} was never written"))
+    }
+
+    pub fn get_buffer(&self, slot: &str) -> &Buffer {
+        self.buffers
+            .get(slot)
+            .unwrap_or_else(|| panic!("Render graph buffer slot {slot} was never written"))
+    }
+}
+
+// A node can record anything into the encoder, its own render passes, copies or compute work
+pub trait RenderNode {
+    fn run(&self, context: &mut RenderContext, world: &World, singletons: &Singletons);
+}
+
+// A render pass into one texture slot, the renderers inside are drawn in the order they were added
+pub struct PassNode {
+    pub label: String,
+    pub target: String,
+    // None loads whatever is already in the target
+    pub clear_color: Option<wgpu::Color>,
+    renderers: Vec<Box<dyn Renderer>>,
+}
+
+impl PassNode {
+    pub fn new(label: &str, target: &str, clear_color: Option<wgpu::Color>) -> Self {
+        Self {
+            label: label.to_string(),
+            target: target.to_string(),
+            clear_color,
+            renderers: Vec::new(),
+        }
+    }
+
+    fn run(&self, context: &mut RenderContext, world: &World, singletons: &Singletons) {
+        let view = context.textures.get(&self.target).unwrap_or_else(|| {
+            panic!("Pass {} renders into {} which was never written", self.label, self.target)
+        });
+
+        let load = match self.clear_color {
+            Some(color) => wgpu::LoadOp::Clear(color),
+            None => wgpu::LoadOp::Load,
+        };
+
+        let mut render_pass = context.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(&self.label),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        // dynamic dispatch lol
+        for renderer in self.renderers.iter() {
+            renderer.render(&mut render_pass, world, singletons);
+        }
+    }
+}
+
+enum NodeKind {
+    Pass(PassNode),
+    Custom(Box<dyn RenderNode>),
+}
+
+pub struct GraphNode {
+    name: String,
+    reads: Vec<String>,
+    writes: Vec<String>,
+    after: Vec<String>,
+    kind: NodeKind,
+}
+
+impl GraphNode {
+    pub fn reads(&mut self, slot: &str) -> &mut Self {
+        self.reads.push(slot.to_string());
+        self
+    }
+
+    pub fn writes(&mut self, slot: &str) -> &mut Self {
+        self.writes.push(slot.to_string());
+        self
+    }
+
+    // For ordering that doesn't go through a slot, e.g. uploading uniforms before a pass uses them
+    pub fn after(&mut self, node: &str) -> &mut Self {
+        self.after.push(node.to_string());
+        self
+    }
+}
+
+#[derive(Default)]
+pub struct RenderGraph {
+    nodes: Vec<GraphNode>,
+}
+
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self { nodes: Vec::new() }
+    }
+
+    pub fn add_node(&mut self, name: &str, node: Box<dyn RenderNode>) -> &mut GraphNode {
+        self.push_node(name, NodeKind::Custom(node))
+    }
+
+    // A pass without clear color has to come after whoever wrote the target before it
+    pub fn add_pass(&mut self, pass: PassNode) -> &mut GraphNode {
+        let name = pass.label.clone();
+        let target = pass.target.clone();
+        let loads = pass.clear_color.is_none();
+
+        let node = self.push_node(&name, NodeKind::Pass(pass));
+        node.writes(&target);
+
+        if loads {
+            node.reads(&target);
+        }
+
+        node
+    }
+
+    fn push_node(&mut self, name: &str, kind: NodeKind) -> &mut GraphNode {
+        assert!(
+            self.get_node_index(name).is_none(),
+            "Render graph already has a node called {name}"
+        );
+
+        self.nodes.push(GraphNode {
+            name: name.to_string(),
+            reads: Vec::new(),
+            writes: Vec::new(),
+            after: Vec::new(),
+            kind,
+        });
+
+        self.nodes.last_mut().unwrap()
+    }
+
+    pub fn add_to_pass(&mut self, pass: &str, renderer: Box<dyn Renderer>) {
+        let index = self
+            .get_node_index(pass)
+            .unwrap_or_else(|| panic!("Render graph has no pass called {pass}"));
+
+        match &mut self.nodes[index].kind {
+            NodeKind::Pass(pass) => pass.renderers.push(renderer),
+            NodeKind::Custom(_) => panic!("Render graph node {pass} is not a pass"),
+        }
+    }
+
+    // Moves a pass into another texture slot, e.g. the main pass into an intermediate texture for
+    // post processing. Whatever reads or writes the old slot is not touched.
+    pub fn set_pass_target(&mut self, pass: &str, target: &str) {
+        let index = self
+            .get_node_index(pass)
+            .unwrap_or_else(|| panic!("Render graph has no pass called {pass}"));
+
+        let node = &mut self.nodes[index];
+
+        let NodeKind::Pass(pass_node) = &mut node.kind else {
+            panic!("Render graph node {pass} is not a pass");
+        };
+
+        let old_target = std::mem::replace(&mut pass_node.target, target.to_string());
+
+        for slot in node.reads.iter_mut().chain(node.writes.iter_mut()) {
+            if *slot == old_target {
+                *slot = target.to_string();
+            }
+        }
+    }
+
+    pub fn get_node_mut(&mut self, name: &str) -> Option<&mut GraphNode> {
+        let index = self.get_node_index(name)?;
+        Some(&mut self.nodes[index])
+    }
+
+    fn get_node_index(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|node| node.name == name)
+    }
+
+    // A node runs after
+    //  - every node named in its `after`
+    //  - every node writing a slot it reads. If both of them also read that slot (two passes drawing
+    //    on top of the same texture) the one added first goes first.
+    // Otherwise nodes keep the order they were added in.
+    pub fn get_order(&self) -> Vec<usize> {
+        let mut dependencies: Vec<HashSet<usize>> = vec![HashSet::new(); self.nodes.len()];
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            for name in node.after.iter() {
+                let other = self.get_node_index(name).unwrap_or_else(|| {
+                    panic!("Render graph node {} runs after {name} which does not exist", node.name)
+                });
+
+                dependencies[index].insert(other);
+            }
+
+            for slot in node.reads.iter() {
+                let writers: Vec<usize> = self
+                    .nodes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, other)| other.writes.contains(slot))
+                    .map(|(other_index, _)| other_index)
+                    .collect();
+
+                // The surface is there from the start
+                if writers.is_empty() && slot != SURFACE_SLOT {
+                    panic!("Render graph node {} reads {slot} but nothing writes it", node.name);
+                }
+
+                for other in writers {
+                    let other_reads = self.nodes[other].reads.contains(slot);
+
+                    if other != index && (!other_reads || other < index) {
+                        dependencies[index].insert(other);
+                    }
+                }
+            }
+        }
+
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut done = vec![false; self.nodes.len()];
+
+        while order.len() < self.nodes.len() {
+            let next = (0..self.nodes.len())
+                .find(|&index| !done[index] && dependencies[index].iter().all(|&other| done[other]));
+
+            let Some(next) = next else {
+                let stuck: Vec<&str> = (0..self.nodes.len())
+                    .filter(|&index| !done[index])
+                    .map(|index| self.nodes[index].name.as_str())
+                    .collect();
+
+                panic!("Render graph has a cycle between {}", stuck.join(", "));
+            };
+
+            done[next] = true;
+            order.push(next);
+        }
+
+        order
+    }
+
+    pub fn run(
+        &self,
+        encoder: &mut CommandEncoder,
+        surface: TextureView,
+        world: &World,
+        singletons: &Singletons,
+    ) {
+        let mut context = RenderContext {
+            encoder,
+            textures: HashMap::new(),
+            buffers: HashMap::new(),
+        };
+
+        context.textures.insert(SURFACE_SLOT.to_string(), surface);
+
+        for index in self.get_order() {
+            match &self.nodes[index].kind {
+                NodeKind::Pass(pass) => pass.run(&mut context, world, singletons),
+                NodeKind::Custom(node) => node.run(&mut context, world, singletons),
+            }
+        }
+    }
+}
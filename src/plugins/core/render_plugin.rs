@@ -1,24 +1,27 @@
+use crate::app::PluginInfo;
+use crate::plugins::core::window::window_plugin::WindowPlugin;
 use crate::{
-    ecs::singletons::{self, Singletons},
+    ecs::singletons::Singletons,
     storage::Storage,
 };
-use std::{num::NonZeroU32, rc::Rc, string, sync::Arc};
+use std::path::PathBuf;
 
-use bytemuck::{Pod, Zeroable};
 use hashbrown::HashMap;
-use log::info;
+use glam::UVec2;
+use log::{info, warn};
 use wgpu::{
-    include_wgsl,
-    util::{BufferInitDescriptor, DeviceExt},
-    BindGroup, BindGroupDescriptor, CommandEncoder, Device, DeviceDescriptor, Queue, RenderPass,
-    RequestAdapterOptions, Surface, SurfaceConfiguration, SurfaceTexture,
+    util::DeviceExt, Device, DeviceDescriptor, Queue, RenderPass,
+    RequestAdapterOptions, Surface, SurfaceConfiguration,
 };
 use winit::window::Window;
 
+use super::render_graph::{PassNode, RenderGraph, MAIN_PASS, SURFACE_SLOT};
+use super::window::{window_events::WindowState, window_settings::WindowSettings};
+
 use crate::{
     app::{App, Plugin},
-    ecs::world::{self, World},
-    plugins::renderer_plugins::texture::Texture,
+    ecs::world::World,
+    plugins::{asset_types::image::Image, renderer_plugins::texture::Texture},
 };
 
 pub trait Renderer {
@@ -35,21 +38,23 @@ pub struct MeshBuffers {
     pub vertex_buffer: wgpu::Buffer,
 }
 
+// Where frames end up. Offscreen is used when there is no window, e.g. headless golden image tests
+pub enum RenderTarget {
+    Surface(Surface),
+    Offscreen(wgpu::Texture),
+}
+
 pub struct Gpu {
-    pub surface: Surface,
+    pub target: RenderTarget,
     pub queue: Queue,
     pub device: Device,
-    pub surface_config: wgpu::SurfaceConfiguration,
+    pub surface_config: wgpu::SurfaceConfiguration, // Size and format of the target, offscreen too
 
     pub texture_bing_group_map: HashMap<u64, wgpu::BindGroup>, // Why bind group and not texture buffer??
     pub texture_map: HashMap<u64, Texture>,
     pub texture_bind_group_layout: wgpu::BindGroupLayout, // Sprite render needs this
 
     mesh_data_map: HashMap<u64, MeshBuffers>,
-
-    draw_index_buffers: [wgpu::Buffer; 512],
-    draw_index_bing_group: [wgpu::BindGroup; 512],
-    pub draw_index_bind_group_layout: wgpu::BindGroupLayout,
 }
 
 impl Gpu {
@@ -114,10 +119,6 @@ impl Gpu {
         // Now I need to create a bind group
     }
 
-    pub fn get_draw_index_bind_group(&self, index: usize) -> &wgpu::BindGroup {
-        &self.draw_index_bing_group[index]
-    }
-
     pub fn create_mesh_buffers(&mut self, id: u64, vertex_data: &[u8], index_buffer: &[u8]) {
         if self.mesh_data_map.contains_key(&id) {
             return;
@@ -158,16 +159,141 @@ impl Gpu {
         let upper_size = (size - 1) / buffer_length;
         (upper_size + 1) * buffer_length
     }
+
+    // Call after changing surface_config
+    pub fn configure_target(&mut self) {
+        match &mut self.target {
+            RenderTarget::Surface(surface) => surface.configure(&self.device, &self.surface_config),
+            RenderTarget::Offscreen(texture) => {
+                *texture = create_offscreen_texture(&self.device, &self.surface_config)
+            }
+        }
+    }
+
+    // Blocks until the gpu is done, fine for screenshots and tests, too slow for every frame
+    pub fn read_texture(&self, texture: &wgpu::Texture) -> Image {
+        let width = texture.width();
+        let height = texture.height();
+
+        // Rows in the buffer have to be aligned to 256 bytes
+        let unpadded_bytes_per_row = width * 4;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+
+        let buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback buffer"),
+            size: (padded_bytes_per_row * height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Readback encoder"),
+            });
+
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+        self.device.poll(wgpu::Maintain::Wait);
+
+        let mapped = slice.get_mapped_range();
+        let mut data = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+
+        for row in mapped.chunks(padded_bytes_per_row as usize) {
+            data.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+
+        drop(mapped);
+        buffer.unmap();
+
+        // Surfaces are bgra on most platforms, Image is always rgba
+        if matches!(
+            texture.format(),
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb
+        ) {
+            for pixel in data.chunks_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        Image::new(UVec2::new(width, height), data)
+    }
 }
 
-pub fn render_function(storage: &mut Storage, renderers: &Vec<Box<dyn Renderer>>) {
+fn create_offscreen_texture(device: &Device, config: &SurfaceConfiguration) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Offscreen target"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    })
+}
+
+#[derive(Default)]
+pub struct ScreenshotRequests {
+    pending: Vec<PathBuf>,
+}
+
+impl ScreenshotRequests {
+    // Saved as png at the end of the frame
+    pub fn request(&mut self, path: impl Into<PathBuf>) {
+        self.pending.push(path.into());
+    }
+}
+
+// Renders the graph one more time into a new texture and reads it back
+pub fn capture_frame(storage: &Storage, render_graph: &RenderGraph) -> Image {
     let gpu = storage.singletons.get::<Gpu>().unwrap();
 
-    let output = gpu.surface.get_current_texture().unwrap();
+    let texture = create_offscreen_texture(&gpu.device, &gpu.surface_config);
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut encoder = gpu
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Capture Encoder"),
+        });
+
+    render_graph.run(&mut encoder, view, &storage.world, &storage.singletons);
+    gpu.queue.submit(std::iter::once(encoder.finish()));
 
-    let view = output
-        .texture
-        .create_view(&wgpu::TextureViewDescriptor::default());
+    gpu.read_texture(&texture)
+}
+
+impl App {
+    pub fn capture_frame(&self) -> Image {
+        capture_frame(&self.storage, &self.render_graph)
+    }
+}
+
+pub fn render_function(storage: &mut Storage, render_graph: &RenderGraph) {
+    let gpu = storage.singletons.get::<Gpu>().unwrap();
 
     let mut encoder = gpu
         .device
@@ -175,112 +301,164 @@ pub fn render_function(storage: &mut Storage, renderers: &Vec<Box<dyn Renderer>>
             label: Some("Render Encoder"),
         });
 
-    let mut render_pass = (encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-        label: Some("Render Pass"),
-        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-            view: &view,
-            resolve_target: None,
-            ops: wgpu::Operations {
-                load: wgpu::LoadOp::Clear(wgpu::Color {
-                    r: 1.0,
-                    g: 1.0,
-                    b: 1.0,
-                    a: 1.0,
-                }),
-                store: wgpu::StoreOp::Store,
-            },
-        })],
-        depth_stencil_attachment: None,
-        timestamp_writes: None,
-        occlusion_query_set: None,
-    }));
-
-    // dynamic dispatch lol
-    for renderer in renderers.iter() {
-        renderer.render(&mut render_pass, &storage.world, &storage.singletons);
+    match &gpu.target {
+        RenderTarget::Surface(surface) => {
+            let output = match surface.get_current_texture() {
+                Ok(output) => output,
+                // Happens around resizes, the next Resize fixes it
+                Err(err) => {
+                    warn!("Skipping frame: {err}");
+                    return;
+                }
+            };
+
+            let view = output
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+
+            render_graph.run(&mut encoder, view, &storage.world, &storage.singletons);
+
+            gpu.queue.submit(std::iter::once(encoder.finish()));
+            output.present();
+        }
+        RenderTarget::Offscreen(texture) => {
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+            render_graph.run(&mut encoder, view, &storage.world, &storage.singletons);
+
+            gpu.queue.submit(std::iter::once(encoder.finish()));
+        }
     }
 
-    drop(render_pass);
+    let screenshots = storage.singletons.get_mut::<ScreenshotRequests>().unwrap();
+    let pending = std::mem::take(&mut screenshots.pending);
 
-    // I need to get gpu again because borrow checker doesn't allow me to use above gpu again
-    // How is the turn table borrow checker
-    let gpu = storage.singletons.get::<Gpu>().unwrap();
-    gpu.queue.submit(std::iter::once(encoder.finish()));
+    for path in pending {
+        let image = capture_frame(storage, render_graph);
+
+        match image.save_png(&path) {
+            Ok(_) => info!("Screenshot saved to {}", path.display()),
+            Err(err) => warn!("Could not save screenshot to {}: {err}", path.display()),
+        }
+    }
+}
+
+fn request_adapter(instance: &wgpu::Instance, surface: Option<&Surface>) -> wgpu::Adapter {
+    let adapter_options = RequestAdapterOptions {
+        power_preference: wgpu::PowerPreference::HighPerformance,
+        force_fallback_adapter: false,
+        compatible_surface: surface,
+    };
+
+    if let Some(adapter) = pollster::block_on(instance.request_adapter(&adapter_options)) {
+        return adapter;
+    }
+
+    // Software rendering, slow but lets CI machines without a gpu render
+    warn!("No gpu adapter found, trying the fallback adapter");
+
+    let adapter_options = RequestAdapterOptions {
+        force_fallback_adapter: true,
+        ..adapter_options
+    };
 
-    output.present();
+    pollster::block_on(instance.request_adapter(&adapter_options))
+        .expect("No gpu adapter found, not even a fallback one")
 }
 
 pub struct RenderPlugin;
 
 impl Plugin for RenderPlugin {
-    fn build(app: &mut App) {
-        let window = app.storage.singletons.get::<Window>().unwrap();
+    // Without a window everything is rendered into an offscreen texture instead
+    fn optional_dependencies() -> Vec<PluginInfo> {
+        vec![PluginInfo::of::<WindowPlugin>()]
+    }
 
+    fn build(app: &mut App) {
+        let window = app.storage.singletons.get::<Window>();
+        let vsync = app
+            .storage
+            .singletons
+            .get::<WindowSettings>()
+            .map(|settings| settings.vsync)
+            .unwrap_or_default();
+
+        // WGPU_BACKEND=gl etc. picks something else, useful on CI
         let instance_descriptor = wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::PRIMARY,
+            backends: wgpu::util::backend_bits_from_env().unwrap_or(wgpu::Backends::PRIMARY),
 
             ..Default::default()
         };
 
         let instance = wgpu::Instance::new(instance_descriptor);
 
-        let surface = unsafe {
-            let surface = instance.create_surface(window);
-            if let Err(err) = surface {
-                panic!("Create surface error! {err}");
-                None
-            } else {
-                surface.ok()
-            }
-        }
-        .unwrap();
+        let surface = window.map(|window| unsafe {
+            instance
+                .create_surface(window)
+                .unwrap_or_else(|err| panic!("Create surface error! {err}"))
+        });
 
-        let adapter_options = RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::HighPerformance,
-            force_fallback_adapter: false,
-            compatible_surface: Some(&surface),
+        let (width, height) = match window {
+            Some(window) => (window.inner_size().width, window.inner_size().height),
+            None => {
+                let window_state = app
+                    .storage
+                    .singletons
+                    .get::<WindowState>()
+                    .copied()
+                    .unwrap_or_default();
+
+                (window_state.width, window_state.height)
+            }
         };
 
-        for adapter in instance.enumerate_adapters(wgpu::Backends::VULKAN) {
-            info!("{:?}", adapter.get_info());
-        }
-
-        let adapter = pollster::block_on(instance.request_adapter(&adapter_options)).unwrap();
+        let adapter = request_adapter(&instance, surface.as_ref());
 
         info!("Selected adapter {:?}", adapter.get_info());
 
+        // Fallback adapters don't always have these
+        let optional_features =
+            wgpu::Features::POLYGON_MODE_LINE | wgpu::Features::POLYGON_MODE_POINT;
+
         let device_descriptor = DeviceDescriptor {
             label: None,
-            features: wgpu::Features::default()
-                | wgpu::Features::POLYGON_MODE_LINE
-                | wgpu::Features::POLYGON_MODE_POINT,
+            features: wgpu::Features::default() | (adapter.features() & optional_features),
             limits: wgpu::Limits::default(), // TODO: learn about this
         };
 
         let (device, queue) =
             pollster::block_on(adapter.request_device(&device_descriptor, None)).unwrap();
 
-        let surface_caps = surface.get_capabilities(&adapter);
+        let surface_format = match &surface {
+            Some(surface) => {
+                let surface_caps = surface.get_capabilities(&adapter);
 
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .copied()
-            .filter(|f| f.is_srgb())
-            .next()
-            .unwrap_or(surface_caps.formats[0]);
+                surface_caps
+                    .formats
+                    .iter()
+                    .copied().find(|f| f.is_srgb())
+                    .unwrap_or(surface_caps.formats[0])
+            }
+            None => wgpu::TextureFormat::Rgba8UnormSrgb,
+        };
 
         let surface_config = SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
-            width: window.inner_size().width,
-            height: window.inner_size().height,
-            present_mode: wgpu::PresentMode::AutoNoVsync,
+            width,
+            height,
+            present_mode: present_mode(vsync),
             alpha_mode: wgpu::CompositeAlphaMode::Auto,
             view_formats: vec![],
         };
 
-        surface.configure(&device, &surface_config);
+        let target = match surface {
+            Some(surface) => {
+                surface.configure(&device, &surface_config);
+                RenderTarget::Surface(surface)
+            }
+            None => RenderTarget::Offscreen(create_offscreen_texture(&device, &surface_config)),
+        };
 
         let texture_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
@@ -307,46 +485,8 @@ impl Plugin for RenderPlugin {
                 label: Some("texture_bind_group_layout"),
             });
 
-        let draw_index_bind_group_layout =
-            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Draw index bind group layout"),
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                }],
-            });
-
-        let mut draw_index_buffers = Vec::with_capacity(512);
-        let mut draw_index_bing_group = Vec::with_capacity(512);
-
-        for i in 0..512 {
-            draw_index_buffers.push(device.create_buffer_init(&BufferInitDescriptor {
-                label: Some(format!("Draw index {i} buffer").as_str()),
-                contents: bytemuck::cast_slice(&[i]),
-                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            }));
-
-            draw_index_bing_group.push(device.create_bind_group(&BindGroupDescriptor {
-                label: Some(format!("Draw index {i} bind group").as_str()),
-                layout: &draw_index_bind_group_layout,
-                entries: &[wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: draw_index_buffers[i].as_entire_binding(),
-                }],
-            }));
-        }
-
-        let draw_index_buffers = draw_index_buffers.try_into().unwrap();
-        let draw_index_bing_group = draw_index_bing_group.try_into().unwrap();
-
         let gpu = Gpu {
-            surface,
+            target,
             queue,
             device,
             surface_config,
@@ -355,28 +495,55 @@ impl Plugin for RenderPlugin {
             texture_map: HashMap::new(),
             texture_bind_group_layout,
 
-            draw_index_bind_group_layout,
-            draw_index_buffers,
-            draw_index_bing_group,
-
             mesh_data_map: HashMap::new(),
         };
 
+        app.render_graph.add_pass(PassNode::new(
+            MAIN_PASS,
+            SURFACE_SLOT,
+            Some(wgpu::Color::WHITE),
+        ));
+
         app.set_renderer(render_function);
         app.storage.singletons.insert(gpu);
+        app.storage.singletons.insert(ScreenshotRequests::default());
+        app.schedular
+            .add_system(crate::app::SystemStage::Resize, on_resize);
         app.schedular
-            .add_system(crate::app::SystemStage::Resize, on_resize)
+            .add_system(crate::app::SystemStage::PreUpdate, apply_vsync);
         // app.schedular.add_system(1, draw);
     }
 }
 
+fn present_mode(vsync: bool) -> wgpu::PresentMode {
+    if vsync {
+        wgpu::PresentMode::AutoVsync
+    } else {
+        wgpu::PresentMode::AutoNoVsync
+    }
+}
+
+fn apply_vsync(world: &mut Storage) {
+    let Some((gpu, settings)) = world.singletons.get_many_mut::<(Gpu, WindowSettings)>() else {
+        return;
+    };
+
+    let present_mode = present_mode(settings.vsync);
+
+    if gpu.surface_config.present_mode == present_mode {
+        return;
+    }
+
+    gpu.surface_config.present_mode = present_mode;
+    gpu.configure_target();
+}
+
 fn on_resize(world: &mut Storage) {
-    let window = world.singletons.get::<Window>().unwrap();
-    let size = window.inner_size();
+    let size = *world.singletons.get::<WindowState>().unwrap();
 
     let gpu = world.singletons.get_mut::<Gpu>().unwrap();
     gpu.surface_config.width = size.width;
     gpu.surface_config.height = size.height;
 
-    gpu.surface.configure(&gpu.device, &gpu.surface_config);
+    gpu.configure_target();
 }
@@ -0,0 +1,132 @@
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, Instant, SystemTime},
+};
+
+use log::info;
+
+use crate::{
+    app::{Plugin, PluginInfo, SystemStage},
+    ecs::event_bus::WorldEvent,
+    plugins::asset_types::shader::Shader,
+    storage::Storage,
+};
+
+use super::asset_storage::{AssetRef, AssetStorage, AssetStoragePlugin};
+
+// Polling the modified time of a handful of files is cheap, no need for a file watcher crate
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+// Emitted after the shader asset got the new source. Renderers using it rebuild their pipeline.
+pub struct ShaderChanged {
+    pub shader: AssetRef<Shader>,
+    pub path: String,
+}
+
+impl WorldEvent for ShaderChanged {}
+
+struct WatchedShader {
+    path: String,
+    shader: AssetRef<Shader>,
+    modified: Option<SystemTime>,
+}
+
+pub struct ShaderWatcher {
+    // Shaders are still loaded from disk when this is off, they just don't reload
+    pub enabled: bool,
+    shaders: Vec<WatchedShader>,
+    last_check: Instant,
+}
+
+impl Default for ShaderWatcher {
+    fn default() -> Self {
+        Self {
+            enabled: cfg!(debug_assertions),
+            shaders: Vec::new(),
+            last_check: Instant::now(),
+        }
+    }
+}
+
+impl ShaderWatcher {
+    // Loads the shader from `path` and watches it. When the file is not there, e.g. the game runs
+    // somewhere else than the repo, `fallback` is used instead, that's the source built into the binary.
+    pub fn load(
+        &mut self,
+        assets: &mut AssetStorage,
+        path: &str,
+        fallback: &str,
+    ) -> AssetRef<Shader> {
+        if !Path::new(path).exists() {
+            assets.insert(Shader::new(fallback), path);
+            return assets.get::<Shader>(path).unwrap();
+        }
+
+        let shader = assets.get::<Shader>(path).unwrap();
+
+        self.shaders.push(WatchedShader {
+            path: path.to_string(),
+            shader: shader.clone(),
+            modified: get_modified_time(path),
+        });
+
+        shader
+    }
+}
+
+fn get_modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+pub struct ShaderReloadPlugin;
+
+impl Plugin for ShaderReloadPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![PluginInfo::of::<AssetStoragePlugin>()]
+    }
+
+    fn build(app: &mut crate::app::App) {
+        app.storage.singletons.insert(ShaderWatcher::default());
+        app.schedular
+            .add_system(SystemStage::PreUpdate, reload_changed_shaders);
+    }
+}
+
+fn reload_changed_shaders(storage: &mut Storage) {
+    let (watcher, assets) = storage
+        .singletons
+        .get_many_mut::<(ShaderWatcher, AssetStorage)>()
+        .unwrap();
+
+    if !watcher.enabled || watcher.last_check.elapsed() < CHECK_INTERVAL {
+        return;
+    }
+
+    watcher.last_check = Instant::now();
+
+    let mut events = Vec::new();
+
+    for watched in watcher.shaders.iter_mut() {
+        let modified = get_modified_time(&watched.path);
+
+        if modified == watched.modified {
+            continue;
+        }
+
+        watched.modified = modified;
+
+        if assets.reload::<Shader>(&watched.path) {
+            info!("Reloading shader {}", watched.path);
+
+            events.push(ShaderChanged {
+                shader: watched.shader.clone(),
+                path: watched.path.clone(),
+            });
+        }
+    }
+
+    for event in events {
+        storage.emit(event);
+    }
+}
@@ -1,13 +1,17 @@
 use crate::storage::Storage;
 use std::time::Instant;
 
-use crate::{app::Plugin, ecs::world::World};
+use crate::app::Plugin;
 
 pub struct Time {
     pub frame_count: u64,
     pub delta_time: f32,
     pub total_time: f32,
 
+    // When set every frame advances by exactly this much instead of the real elapsed time.
+    // Used by the headless runner so simulations don't depend on how fast the machine is.
+    pub fixed_delta_time: Option<f32>,
+
     last_frame_instant: Instant,
 }
 
@@ -19,6 +23,7 @@ impl Plugin for TimerPlugin {
             frame_count: 0,
             delta_time: 0.0,
             total_time: 0.0,
+            fixed_delta_time: None,
             last_frame_instant: Instant::now(),
         };
 
@@ -31,7 +36,12 @@ impl Plugin for TimerPlugin {
 fn update_timer(world: &mut Storage) {
     let time = world.singletons.get_mut::<Time>().unwrap();
 
-    time.delta_time = time.last_frame_instant.elapsed().as_secs_f32();
+    time.delta_time = match time.fixed_delta_time {
+        Some(fixed_delta_time) => fixed_delta_time,
+        None => time.last_frame_instant.elapsed().as_secs_f32(),
+    };
+
+    time.frame_count += 1;
     time.total_time += time.delta_time;
     time.last_frame_instant = Instant::now();
 }
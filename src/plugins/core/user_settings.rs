@@ -0,0 +1,320 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use log::{info, warn};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    app::{App, Plugin, SystemStage},
+    storage::Storage,
+};
+
+use super::{
+    input::keycode::KeyCode,
+    window::{
+        window_events::WindowState,
+        window_settings::{WindowMode, WindowSettings},
+    },
+};
+
+// Stored in <config dir>/pixel-rs/settings.toml, e.g. ~/.config/pixel-rs/settings.toml on linux
+const SETTINGS_DIR: &str = "pixel-rs";
+const SETTINGS_FILE: &str = "settings.toml";
+
+// Where UserSettingsPlugin loads the settings from and saves them to.
+// Insert one before the plugins are built to change it, HeadlessPlugins uses InMemory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum UserSettingsStore {
+    #[default]
+    ConfigDir,
+    File(PathBuf),
+    // Defaults only, nothing is read or written
+    InMemory,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UserSettings {
+    pub window: WindowPreferences,
+    pub camera: CameraPreferences,
+    pub key_bindings: KeyBindings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowPreferences {
+    pub width: u32,
+    pub height: u32,
+    pub mode: WindowMode,
+    pub vsync: bool,
+    // Fields that were in the settings file, only those are copied into WindowSettings at startup
+    #[serde(skip)]
+    pub loaded_fields: Vec<String>,
+}
+
+impl Default for WindowPreferences {
+    fn default() -> Self {
+        let window_settings = WindowSettings::default();
+
+        Self {
+            width: window_settings.width,
+            height: window_settings.height,
+            mode: window_settings.mode,
+            vsync: window_settings.vsync,
+            loaded_fields: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraPreferences {
+    pub pan_speed: f32,
+}
+
+impl Default for CameraPreferences {
+    fn default() -> Self {
+        Self { pan_speed: 1.0 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyBindings {
+    pub pause: KeyCode,
+    pub fullscreen: KeyCode,
+    pub screenshot: KeyCode,
+    pub debug_draw: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            pause: KeyCode::Escape,
+            fullscreen: KeyCode::F11,
+            screenshot: KeyCode::F12,
+            debug_draw: KeyCode::F3,
+        }
+    }
+}
+
+impl UserSettings {
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(SETTINGS_DIR).join(SETTINGS_FILE))
+    }
+
+    pub fn load() -> Self {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => {
+                warn!("No config directory, using default settings");
+                Self::default()
+            }
+        }
+    }
+
+    // Never fails, anything missing or broken falls back to the default with a warning
+    pub fn load_from(path: &Path) -> Self {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(_) => {
+                info!("No settings at {}, using defaults", path.display());
+                return Self::default();
+            }
+        };
+
+        let table = match text.parse::<toml::Table>() {
+            Ok(table) => table,
+            Err(err) => {
+                warn!("Could not parse {}: {err}, using default settings", path.display());
+                return Self::default();
+            }
+        };
+
+        for key in table.keys() {
+            if !["window", "camera", "key_bindings"].contains(&key.as_str()) {
+                warn!("Unknown settings section [{key}]");
+            }
+        }
+
+        let (mut window, loaded_fields) = load_section::<WindowPreferences>(&table, "window");
+        window.loaded_fields = loaded_fields;
+
+        Self {
+            window,
+            camera: load_section(&table, "camera").0,
+            key_bindings: load_section(&table, "key_bindings").0,
+        }
+    }
+
+    pub fn save(&self) {
+        match Self::path() {
+            Some(path) => self.save_to(&path),
+            None => warn!("No config directory, settings are not saved"),
+        }
+    }
+
+    pub fn save_to(&self, path: &Path) {
+        let text = toml::to_string_pretty(self).unwrap();
+
+        let result = fs::create_dir_all(path.parent().unwrap()).and_then(|_| fs::write(path, text));
+
+        match result {
+            Ok(_) => info!("Settings saved to {}", path.display()),
+            Err(err) => warn!("Could not save settings to {}: {err}", path.display()),
+        }
+    }
+}
+
+// Fields are taken one by one on top of the defaults, so a single bad value doesn't throw away the rest.
+// Also returns the names of the fields that were taken from the file.
+fn load_section<T: Serialize + DeserializeOwned + Default>(
+    table: &toml::Table,
+    name: &str,
+) -> (T, Vec<String>) {
+    let mut merged = toml::Table::try_from(T::default()).unwrap();
+    let mut loaded_fields = Vec::new();
+
+    let Some(section) = table.get(name) else {
+        return (T::default(), loaded_fields);
+    };
+
+    let Some(section) = section.as_table() else {
+        warn!("Settings [{name}] is not a section, using defaults");
+        return (T::default(), loaded_fields);
+    };
+
+    for (key, value) in section {
+        if !merged.contains_key(key) {
+            warn!("Unknown setting {name}.{key}");
+            continue;
+        }
+
+        let mut candidate = merged.clone();
+        candidate.insert(key.clone(), value.clone());
+
+        match candidate.clone().try_into::<T>() {
+            Ok(_) => {
+                merged = candidate;
+                loaded_fields.push(key.clone());
+            }
+            Err(err) => warn!("Invalid setting {name}.{key} = {value}, using default: {err}"),
+        }
+    }
+
+    (merged.try_into().unwrap(), loaded_fields)
+}
+
+// Has to be built before WindowPlugin so the window is created with the saved size.
+// Only what is in the settings file overrides a WindowSettings inserted by the game.
+pub struct UserSettingsPlugin;
+
+impl Plugin for UserSettingsPlugin {
+    fn build(app: &mut App) {
+        let store = app
+            .storage
+            .singletons
+            .get::<UserSettingsStore>()
+            .cloned()
+            .unwrap_or_default();
+
+        let user_settings = match &store {
+            UserSettingsStore::ConfigDir => UserSettings::load(),
+            UserSettingsStore::File(path) => UserSettings::load_from(path),
+            UserSettingsStore::InMemory => UserSettings::default(),
+        };
+
+        let mut window_settings = app
+            .storage
+            .singletons
+            .remove::<WindowSettings>()
+            .unwrap_or_default();
+
+        let window = &user_settings.window;
+
+        for field in window.loaded_fields.iter() {
+            match field.as_str() {
+                "width" => window_settings.width = window.width,
+                "height" => window_settings.height = window.height,
+                "mode" => window_settings.mode = window.mode,
+                "vsync" => window_settings.vsync = window.vsync,
+                _ => {}
+            }
+        }
+
+        app.storage.singletons.insert(window_settings);
+        app.storage.singletons.insert(user_settings);
+        app.storage.singletons.insert(store);
+
+        app.schedular
+            .add_system(SystemStage::Shutdown, save_user_settings);
+    }
+}
+
+fn save_user_settings(storage: &mut Storage) {
+    let store = storage.singletons.get::<UserSettingsStore>().cloned().unwrap();
+
+    if store == UserSettingsStore::InMemory {
+        return;
+    }
+
+    let window_settings = storage.singletons.get::<WindowSettings>().cloned();
+    let window_state = storage.singletons.get::<WindowState>().copied();
+
+    let user_settings = storage.singletons.get_mut::<UserSettings>().unwrap();
+
+    if let Some(window_settings) = window_settings {
+        user_settings.window.mode = window_settings.mode;
+        user_settings.window.vsync = window_settings.vsync;
+    }
+
+    // Size of a fullscreen or minimized window is not what we want to open with next time
+    if let Some(window_state) = window_state {
+        if user_settings.window.mode == WindowMode::Windowed && !window_state.minimized {
+            user_settings.window.width = window_state.width;
+            user_settings.window.height = window_state.height;
+        }
+    }
+
+    match store {
+        UserSettingsStore::ConfigDir => user_settings.save(),
+        UserSettingsStore::File(path) => user_settings.save_to(&path),
+        UserSettingsStore::InMemory => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::App,
+        plugins::core::{headless::HeadlessPlugins, input::keycode::KeyCode},
+    };
+
+    use super::*;
+
+    #[test]
+    fn headless_settings_stay_in_memory() {
+        let mut app = App::new();
+        app.register_plugin::<HeadlessPlugins>();
+        app.run_frames(1);
+
+        let store = app.storage.singletons.get::<UserSettingsStore>().unwrap();
+        assert_eq!(*store, UserSettingsStore::InMemory);
+
+        let user_settings = app.storage.singletons.get::<UserSettings>().unwrap();
+        assert_eq!(user_settings.key_bindings.pause, KeyCode::Escape);
+    }
+
+    #[test]
+    fn file_store_saves_and_loads_its_own_path() {
+        let dir = std::env::temp_dir().join(format!("pixel-rs-settings-{}", std::process::id()));
+        let path = dir.join(SETTINGS_FILE);
+
+        let mut user_settings = UserSettings::default();
+        user_settings.camera.pan_speed = 2.5;
+        user_settings.save_to(&path);
+
+        let loaded = UserSettings::load_from(&path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded.camera.pan_speed, 2.5);
+    }
+}
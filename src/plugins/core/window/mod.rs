@@ -1 +1,4 @@
-pub mod window_plugin;
\ No newline at end of file
+pub mod window_events;
+#[cfg(feature = "window")]
+pub mod window_plugin;
+pub mod window_settings;
\ No newline at end of file
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use glam::Vec2;
+
+use crate::ecs::event_bus::WorldEvent;
+
+// Last known window values, kept up to date by the window runner.
+// Read this instead of asking the winit Window, it also exists in headless apps.
+#[derive(Debug, Clone, Copy)]
+pub struct WindowState {
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    pub focused: bool,
+    pub minimized: bool,
+    pub cursor_inside: bool,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        Self {
+            width: 1280,
+            height: 720,
+            scale_factor: 1.0,
+            focused: true,
+            minimized: false,
+            cursor_inside: false,
+        }
+    }
+}
+
+impl WindowState {
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width as f32, self.height as f32)
+    }
+}
+
+// Physical pixels. Minimizing sends a 0x0 resize on some platforms
+#[derive(Debug, Clone, Copy)]
+pub struct WindowResized {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WorldEvent for WindowResized {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WindowFocused {
+    pub focused: bool,
+}
+
+impl WorldEvent for WindowFocused {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ScaleFactorChanged {
+    pub scale_factor: f64,
+}
+
+impl WorldEvent for ScaleFactorChanged {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WindowMinimized {
+    pub minimized: bool,
+}
+
+impl WorldEvent for WindowMinimized {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WindowMoved {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl WorldEvent for WindowMoved {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CursorEntered;
+
+impl WorldEvent for CursorEntered {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CursorLeft;
+
+impl WorldEvent for CursorLeft {}
+
+#[derive(Debug, Clone)]
+pub struct FileDropped {
+    pub path: PathBuf,
+}
+
+impl WorldEvent for FileDropped {}
+
+#[derive(Debug, Clone)]
+pub struct FileHovered {
+    pub path: PathBuf,
+}
+
+impl WorldEvent for FileHovered {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FileHoverCancelled;
+
+impl WorldEvent for FileHoverCancelled {}
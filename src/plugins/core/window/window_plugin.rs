@@ -1,44 +1,87 @@
 use glam::Vec2;
-use log::info;
+use log::{error, info};
 use winit::{
-    dpi::{PhysicalPosition, PhysicalSize},
+    dpi::PhysicalPosition,
     event::{ElementState, Event, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
-    event_loop::{ControlFlow, EventLoop},
-    window::{Window, WindowBuilder},
+    event_loop::EventLoop,
+    window::Window,
 };
 
 use crate::{
-    app::{App, Plugin},
-    plugins::core::input::{
-        self,
-        input_plugin::{self, ButtonState, Input, MouseButtonEvent},
+    app::{App, AppExit, Plugin, PluginInfo, SystemStage},
+    plugins::core::{
+        input::{
+            self,
+            input_plugin::{ButtonState, Input, MouseButtonEvent},
+        },
+        window::window_events::{
+            CursorEntered, CursorLeft, FileDropped, FileHoverCancelled, FileHovered,
+            ScaleFactorChanged, WindowFocused, WindowMoved, WindowState,
+        },
     },
+    storage::Storage,
 };
 
+use super::window_settings::WindowSettings;
+use crate::plugins::core::user_settings::UserSettingsPlugin;
+
 pub struct WindowPlugin;
 
+// What the window currently looks like, compared against WindowSettings to find changes
+struct AppliedWindowSettings(WindowSettings);
+
 fn runner(mut app: App) {
     let event_loop = app.storage.singletons.remove::<EventLoop<()>>().unwrap();
     let w_id = app.storage.singletons.get::<Window>().unwrap().id();
 
-    event_loop.run(move |event, window_target| {
+    let result = event_loop.run(move |event, window_target| {
         // *r_control_flow = ControlFlow::Poll;
 
         match event {
             Event::WindowEvent { window_id, event } if window_id == w_id => match event {
-                WindowEvent::CloseRequested => {
-                    info!("bye bye");
-                    window_target.exit();
+                WindowEvent::CloseRequested => app.storage.emit(AppExit),
+
+                WindowEvent::Resized(physical_size) => {
+                    app.on_resize(physical_size.width, physical_size.height)
+                }
+                WindowEvent::Moved(position) => app.storage.emit(WindowMoved {
+                    x: position.x,
+                    y: position.y,
+                }),
+                WindowEvent::Focused(focused) => {
+                    let window_state = app.storage.singletons.get_mut::<WindowState>().unwrap();
+                    window_state.focused = focused;
+
+                    app.storage.emit(WindowFocused { focused });
+                }
+                WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                    let window_state = app.storage.singletons.get_mut::<WindowState>().unwrap();
+                    window_state.scale_factor = scale_factor;
+
+                    app.storage.emit(ScaleFactorChanged { scale_factor });
+                }
+                WindowEvent::CursorEntered { .. } => {
+                    let window_state = app.storage.singletons.get_mut::<WindowState>().unwrap();
+                    window_state.cursor_inside = true;
+
+                    app.storage.emit(CursorEntered);
                 }
+                WindowEvent::CursorLeft { .. } => {
+                    let window_state = app.storage.singletons.get_mut::<WindowState>().unwrap();
+                    window_state.cursor_inside = false;
 
-                WindowEvent::Resized(physical_size) => app.on_resize(physical_size),
+                    app.storage.emit(CursorLeft);
+                }
+                WindowEvent::DroppedFile(path) => app.storage.emit(FileDropped { path }),
+                WindowEvent::HoveredFile(path) => app.storage.emit(FileHovered { path }),
+                WindowEvent::HoveredFileCancelled => app.storage.emit(FileHoverCancelled),
                 WindowEvent::KeyboardInput {
                     device_id: _,
-                    is_synthetic,
+                    is_synthetic: _,
                     event,
                 } => on_keyboard_input(&mut app, event),
                 WindowEvent::MouseInput {
-                    device_id,
+                    device_id: _,
                     state,
                     button,
                     ..
@@ -47,51 +90,66 @@ fn runner(mut app: App) {
                     on_mouse_input(&mut app, button, state);
                 }
                 WindowEvent::CursorMoved {
-                    device_id,
+                    device_id: _,
                     position,
                     ..
                 } => {
                     on_curser_moved(&mut app, position.cast::<f32>());
                 }
                 WindowEvent::MouseWheel {
-                    device_id,
+                    device_id: _,
                     delta,
-                    phase,
+                    phase: _,
                 } => on_mouse_wheen_input(&mut app, delta),
                 _ => (),
             },
 
             Event::AboutToWait => {
-                app.update();
+                if !app.exit_requested() {
+                    app.update();
+                }
+
+                if app.exit_requested() {
+                    window_target.exit();
+                    return;
+                }
+
                 let window = app.storage.singletons.get::<Window>().unwrap();
                 window.request_redraw();
             }
+
+            // Also reached when the OS ends the loop without a close request
+            Event::LoopExiting => {
+                app.shutdown();
+                info!("bye bye");
+            }
             _ => (),
         }
     });
+
+    if let Err(err) = result {
+        error!("Event loop error: {err}");
+    }
 }
 
 fn on_curser_moved(app: &mut App, cursor_pos: PhysicalPosition<f32>) {
-    let (input, window) = app
+    let (input, window_state) = app
         .storage
         .singletons
-        .get_many_mut::<(Input, Window)>()
+        .get_many_mut::<(Input, WindowState)>()
         .unwrap();
 
-    let window_size = window.inner_size().cast::<f32>();
+    let window_size = window_state.size();
 
-    let x_pos = (cursor_pos.x / window_size.width - 0.5) * 2.0;
-    let y_pos = (0.5 - cursor_pos.y / window_size.height) * 2.0;
+    let x_pos = (cursor_pos.x / window_size.x - 0.5) * 2.0;
+    let y_pos = (0.5 - cursor_pos.y / window_size.y) * 2.0;
 
-    let raw_screen_pos = cursor_pos;
     let screen_pos = Vec2::new(x_pos, y_pos);
 
     input.on_curser_moved(screen_pos);
 }
 
-fn on_mouse_wheen_input(app: &mut App, mouse_wheel_input: MouseScrollDelta) {
-    let input = app.storage.singletons.get_mut::<Input>().unwrap();
-
+fn on_mouse_wheen_input(app: &mut App, _mouse_wheel_input: MouseScrollDelta) {
     // Only If I had Enums for inputs to do it
 
     app.on_mouse_input();
@@ -126,17 +184,61 @@ fn on_keyboard_input(app: &mut App, key_input: KeyEvent) {
 }
 
 impl Plugin for WindowPlugin {
+    // Saved window size and mode end up in WindowSettings before the window is created
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![PluginInfo::of::<UserSettingsPlugin>()]
+    }
+
     fn build(app: &mut crate::app::App) {
         let event_loop = EventLoop::new().unwrap();
-        let window = WindowBuilder::new().build(&event_loop).unwrap();
+        let settings = app
+            .storage
+            .singletons
+            .get::<WindowSettings>()
+            .cloned()
+            .unwrap_or_default();
 
+        let window = settings.create_builder().build(&event_loop).unwrap();
+        settings.apply_after_create(&window);
+
+        let size = window.inner_size();
+        let window_state = WindowState {
+            width: size.width,
+            height: size.height,
+            scale_factor: window.scale_factor(),
+            focused: window.has_focus(),
+            ..Default::default()
+        };
+
+        app.storage.singletons.insert(window_state);
         app.storage.singletons.insert(window);
+        app.storage
+            .singletons
+            .insert(AppliedWindowSettings(settings.clone()));
+        app.storage.singletons.insert(settings);
+
+        app.schedular
+            .add_system(SystemStage::PreUpdate, apply_window_settings);
         app.storage.singletons.insert(event_loop);
 
         app.set_runner(runner);
     }
 }
 
+fn apply_window_settings(storage: &mut Storage) {
+    let (settings, applied, window) = storage
+        .singletons
+        .get_many_mut::<(WindowSettings, AppliedWindowSettings, Window)>()
+        .unwrap();
+
+    if *settings == applied.0 {
+        return;
+    }
+
+    settings.apply_changes(&applied.0, window);
+    applied.0 = settings.clone();
+}
+
 fn convert_winit_state(state: ElementState) -> ButtonState {
     match state {
         ElementState::Pressed => ButtonState::Pressed,
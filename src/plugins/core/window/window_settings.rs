@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "window")]
+use winit::{
+    dpi::PhysicalSize,
+    window::{Fullscreen, Window, WindowBuilder},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowMode {
+    Windowed,
+    BorderlessFullscreen,
+    // Uses the best video mode of the current monitor, falls back to borderless if there is none
+    Fullscreen,
+}
+
+// Insert this before App::run to change the startup values, changing it later is applied
+// at the start of the next frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowSettings {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    pub min_size: Option<(u32, u32)>,
+    pub resizable: bool,
+    pub mode: WindowMode,
+    pub vsync: bool,
+    pub cursor_visible: bool,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            title: String::from("pixel-rs"),
+            width: 1280,
+            height: 720,
+            min_size: None,
+            resizable: true,
+            mode: WindowMode::Windowed,
+            vsync: false,
+            cursor_visible: true,
+        }
+    }
+}
+
+impl WindowSettings {
+    pub fn toggle_fullscreen(&mut self) {
+        self.mode = match self.mode {
+            WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+            WindowMode::BorderlessFullscreen | WindowMode::Fullscreen => WindowMode::Windowed,
+        };
+    }
+}
+
+#[cfg(feature = "window")]
+impl WindowSettings {
+    pub(super) fn create_builder(&self) -> WindowBuilder {
+        let mut builder = WindowBuilder::new()
+            .with_title(&self.title)
+            .with_inner_size(PhysicalSize::new(self.width, self.height))
+            .with_resizable(self.resizable);
+
+        if let Some((width, height)) = self.min_size {
+            builder = builder.with_min_inner_size(PhysicalSize::new(width, height));
+        }
+
+        builder
+    }
+
+    // Things WindowBuilder can't do before the window exists
+    pub(super) fn apply_after_create(&self, window: &Window) {
+        window.set_fullscreen(self.fullscreen(window));
+        window.set_cursor_visible(self.cursor_visible);
+    }
+
+    // Only touches what changed, setting the size every frame would fight with the user resizing the window
+    pub(super) fn apply_changes(&self, old: &WindowSettings, window: &Window) {
+        if self.title != old.title {
+            window.set_title(&self.title);
+        }
+
+        if self.width != old.width || self.height != old.height {
+            let _ = window.request_inner_size(PhysicalSize::new(self.width, self.height));
+        }
+
+        if self.min_size != old.min_size {
+            window.set_min_inner_size(
+                self.min_size
+                    .map(|(width, height)| PhysicalSize::new(width, height)),
+            );
+        }
+
+        if self.resizable != old.resizable {
+            window.set_resizable(self.resizable);
+        }
+
+        if self.mode != old.mode {
+            window.set_fullscreen(self.fullscreen(window));
+        }
+
+        if self.cursor_visible != old.cursor_visible {
+            window.set_cursor_visible(self.cursor_visible);
+        }
+    }
+
+    fn fullscreen(&self, window: &Window) -> Option<Fullscreen> {
+        match self.mode {
+            WindowMode::Windowed => None,
+            WindowMode::BorderlessFullscreen => Some(Fullscreen::Borderless(None)),
+            WindowMode::Fullscreen => {
+                let video_mode = window.current_monitor().and_then(|monitor| {
+                    monitor
+                        .video_modes()
+                        .max_by_key(|mode| (mode.size().width * mode.size().height, mode.refresh_rate_millihertz()))
+                });
+
+                match video_mode {
+                    Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+                    None => Some(Fullscreen::Borderless(None)),
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub mod core;
+#[cfg(feature = "render")]
 pub mod renderer_plugins;
 pub mod other;
 pub mod asset_types;
\ No newline at end of file
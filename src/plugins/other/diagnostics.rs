@@ -0,0 +1,68 @@
+use crate::app::PluginInfo;
+use crate::plugins::core::timer_plugin::TimerPlugin;
+use log::{debug, info};
+
+use crate::{
+    app::{Plugin, SystemStage},
+    plugins::core::timer_plugin::Time,
+    storage::Storage,
+};
+
+pub struct DiagnosticsSettings {
+    // Seconds between two reports
+    pub interval: f32,
+    pub enabled: bool,
+
+    time_since_report: f32,
+}
+
+impl Default for DiagnosticsSettings {
+    fn default() -> Self {
+        Self {
+            interval: 5.0,
+            enabled: true,
+            time_since_report: 0.0,
+        }
+    }
+}
+
+pub struct DiagnosticsPlugin;
+
+impl Plugin for DiagnosticsPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<TimerPlugin>(),
+        ]
+    }
+
+    fn build(app: &mut crate::app::App) {
+        app.storage.singletons.insert(DiagnosticsSettings::default());
+        app.schedular.add_system(SystemStage::Update, report_world_stats);
+    }
+}
+
+// Summary goes to info, every archetype goes to debug so RUST_LOG=debug shows the full picture
+fn report_world_stats(storage: &mut Storage) {
+    let delta_time = storage.singletons.get::<Time>().unwrap().delta_time;
+    let settings = storage.singletons.get_mut::<DiagnosticsSettings>().unwrap();
+
+    if !settings.enabled {
+        return;
+    }
+
+    settings.time_since_report += delta_time;
+
+    if settings.time_since_report < settings.interval {
+        return;
+    }
+
+    settings.time_since_report = 0.0;
+
+    let stats = storage.world.stats();
+
+    info!("World: {stats}");
+
+    for archetype in stats.archetypes.iter() {
+        debug!("  {archetype}");
+    }
+}
@@ -1 +1,3 @@
-pub mod tweener;
\ No newline at end of file
+#[cfg(feature = "tweener")]
+pub mod tweener;
+pub mod diagnostics;
\ No newline at end of file
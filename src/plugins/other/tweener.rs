@@ -1,28 +1,25 @@
+use crate::app::PluginInfo;
+use crate::plugins::core::timer_plugin::TimerPlugin;
 use crate::{storage::Storage, ecs::component::Component};
-use std::default;
 
 use glam::Vec2;
 
 use crate::{
     app::Plugin,
-    ecs::world::World,
-    math::transform2d::{self, Transform2d},
+    math::transform2d::Transform2d,
     plugins::core::timer_plugin::Time,
 };
 
 pub struct TweenerPlugin;
 
 #[derive(Debug)]
+#[derive(Default)]
 pub enum Easing {
+    #[default]
     Linear,
     Sin,
 }
 
-impl Default for Easing {
-    fn default() -> Self {
-        Self::Linear
-    }
-}
 
 #[derive(Debug, Default)]
 struct TweenerState<T: Interpolate + Clone + Default> {
@@ -108,7 +105,7 @@ impl ScaleTweener {
 
 pub struct CustomTweener {
     tweener_state: TweenerState<f32>,
-    pub callback: Box<dyn FnMut(f32) -> ()>,
+    pub callback: Box<dyn FnMut(f32)>,
 }
 
 impl Component for CustomTweener{}
@@ -144,7 +141,7 @@ fn tweener_update(world: &mut Storage) {
     let delta_time = world.singletons.get::<Time>().unwrap().delta_time;
 
     for (transform2d, position_tweener) in world.world.query_mut::<(Transform2d, PositionTweener)>() {
-        if (position_tweener.tweener_state.is_playing) {
+        if position_tweener.tweener_state.is_playing  {
             transform2d.position = position_tweener.tweener_state.tween(delta_time);
         }
     }
@@ -161,6 +158,12 @@ fn tweener_update(world: &mut Storage) {
 }
 
 impl Plugin for TweenerPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<TimerPlugin>(),
+        ]
+    }
+
     fn build(app: &mut crate::app::App) {
         app.storage.world.register_component::<PositionTweener>();
         app.storage.world.register_component::<ScaleTweener>();
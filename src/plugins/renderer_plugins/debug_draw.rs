@@ -0,0 +1,546 @@
+use bytemuck::{Pod, Zeroable};
+use glam::{UVec2, Vec2};
+use wgpu::{Device, PipelineLayout, RenderPipeline, ShaderModule, TextureFormat};
+
+use crate::{
+    app::{Plugin, PluginInfo, SystemStage},
+    ecs::{singletons::Singletons, world::World},
+    math::{
+        color::Color,
+        honeycomb::{Hextor, HEXAGON},
+        rect::Rect,
+    },
+    plugins::{
+        asset_types::{image::Image, shader::Shader},
+        core::{
+            asset_storage::{AssetRef, AssetStorage, AssetStoragePlugin},
+            buffer_vec::BufferVec,
+            camera_plugin::{CameraBindGroup, CameraPlugin},
+            input::input_plugin::{ButtonState, Input, InputEvent, InputPlugin, KeyEvent, KeyboardEvent},
+            render_graph::{RenderContext, RenderNode, SURFACE_SLOT},
+            render_plugin::{Gpu, RenderPlugin},
+            shader_reload::{ShaderChanged, ShaderReloadPlugin, ShaderWatcher},
+            user_settings::{UserSettings, UserSettingsPlugin},
+        },
+    },
+    storage::Storage,
+};
+
+#[cfg(feature = "text")]
+use super::text::{layout_text, Text2d};
+#[cfg(feature = "text")]
+use crate::plugins::asset_types::font::Font;
+
+const CIRCLE_SEGMENTS: usize = 32;
+
+const SHADER_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/plugins/renderer_plugins/debug_draw.wgsl"
+);
+
+// Used when the file is missing or broken at startup
+const SHADER_SOURCE: &str = include_str!("debug_draw.wgsl");
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct DebugVertex {
+    pub position: [f32; 2],
+    pub uv: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl DebugVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x4];
+
+    pub fn new(position: Vec2, color: Color) -> Self {
+        Self {
+            position: position.into(),
+            uv: [0.5, 0.5],
+            color: color.into(),
+        }
+    }
+
+    pub fn decs<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<DebugVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+// Immediate mode shapes in world space. Everything drawn during a frame is shown on top of the
+// scene for that frame only, so systems call these every frame they want something visible.
+pub struct DebugDraw {
+    pub enabled: bool,
+    lines: Vec<DebugVertex>,
+    triangles: Vec<DebugVertex>,
+
+    #[cfg(feature = "text")]
+    font: Option<AssetRef<Font>>,
+    #[cfg(feature = "text")]
+    texts: Vec<(Vec2, Text2d)>,
+}
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        Self {
+            enabled: cfg!(debug_assertions),
+            lines: Vec::new(),
+            triangles: Vec::new(),
+            #[cfg(feature = "text")]
+            font: None,
+            #[cfg(feature = "text")]
+            texts: Vec::new(),
+        }
+    }
+}
+
+impl DebugDraw {
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.triangles.clear();
+        #[cfg(feature = "text")]
+        self.texts.clear();
+    }
+
+    pub fn line(&mut self, start: Vec2, end: Vec2, color: Color) {
+        if !self.enabled {
+            return;
+        }
+
+        self.lines.push(DebugVertex::new(start, color));
+        self.lines.push(DebugVertex::new(end, color));
+    }
+
+    pub fn polyline(&mut self, points: &[Vec2], color: Color) {
+        for pair in points.windows(2) {
+            self.line(pair[0], pair[1], color);
+        }
+    }
+
+    // Like polyline but also connects the last point to the first one
+    pub fn polygon(&mut self, points: &[Vec2], color: Color) {
+        self.polyline(points, color);
+
+        if points.len() > 2 {
+            self.line(points[points.len() - 1], points[0], color);
+        }
+    }
+
+    pub fn circle(&mut self, center: Vec2, radius: f32, color: Color) {
+        let points: Vec<Vec2> = (0..CIRCLE_SEGMENTS)
+            .map(|i| {
+                let angle = i as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                center + Vec2::from_angle(angle) * radius
+            })
+            .collect();
+
+        self.polygon(&points, color);
+    }
+
+    pub fn rect(&mut self, rect: &Rect, color: Color) {
+        self.polygon(&rect.get_corners(), color);
+    }
+
+    // Outline of a hex tile, radius is the same one the tilemap uses
+    pub fn hexagon(&mut self, hextor: Hextor, radius: f32, color: Color) {
+        let center = Vec2::from(hextor.to_vector(radius));
+        let points = HEXAGON.map(|corner| center + Vec2::from(corner) * radius);
+
+        self.polygon(&points, color);
+    }
+
+    pub fn triangle_filled(&mut self, a: Vec2, b: Vec2, c: Vec2, color: Color) {
+        if !self.enabled {
+            return;
+        }
+
+        for point in [a, b, c] {
+            self.triangles.push(DebugVertex::new(point, color));
+        }
+    }
+
+    pub fn rect_filled(&mut self, rect: &Rect, color: Color) {
+        let [a, b, c, d] = rect.get_corners();
+
+        self.triangle_filled(a, b, c, color);
+        self.triangle_filled(a, c, d, color);
+    }
+
+    // Nothing is drawn until a font is set
+    #[cfg(feature = "text")]
+    pub fn set_font(&mut self, font: AssetRef<Font>) {
+        self.font = Some(font);
+    }
+
+    // Position is the top left of the first line
+    #[cfg(feature = "text")]
+    pub fn text(&mut self, position: Vec2, text: &str, size: f32, color: Color) {
+        if !self.enabled {
+            return;
+        }
+
+        let Some(font) = &self.font else {
+            return;
+        };
+
+        let text = Text2d::new(text, font.clone(), size).with_color(color);
+        self.texts.push((position, text));
+    }
+}
+
+struct DebugDrawRendererData {
+    line_pipeline: RenderPipeline,
+    triangle_pipeline: RenderPipeline,
+    render_pipeline_layout: PipelineLayout,
+    shader: AssetRef<Shader>,
+
+    lines: BufferVec<DebugVertex>,
+    triangles: BufferVec<DebugVertex>,
+    // Lines and shapes sample this so they can share the shader with text
+    white_texture: AssetRef<Image>,
+
+    #[cfg(feature = "text")]
+    text_triangles: BufferVec<DebugVertex>,
+    #[cfg(feature = "text")]
+    font_texture_id: Option<u64>,
+}
+
+pub struct DebugDrawPlugin;
+
+impl Plugin for DebugDrawPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<RenderPlugin>(),
+            PluginInfo::of::<CameraPlugin>(),
+            PluginInfo::of::<AssetStoragePlugin>(),
+            PluginInfo::of::<InputPlugin>(),
+            PluginInfo::of::<UserSettingsPlugin>(),
+            PluginInfo::of::<ShaderReloadPlugin>(),
+        ]
+    }
+
+    fn build(app: &mut crate::app::App) {
+        let (gpu, camera_data, assets, shader_watcher) = app
+            .storage
+            .singletons
+            .get_many_mut::<(Gpu, CameraBindGroup, AssetStorage, ShaderWatcher)>()
+            .unwrap();
+
+        let shader = shader_watcher.load(assets, SHADER_PATH, SHADER_SOURCE);
+
+        let render_pipeline_layout =
+            gpu.device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Debug draw pipeline layout"),
+                    bind_group_layouts: &[&camera_data.layout, &gpu.texture_bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+
+        let (line_pipeline, triangle_pipeline) = assets
+            .get_data(&shader)
+            .build_with_fallback(SHADER_PATH, SHADER_SOURCE, |shader| {
+                create_pipelines(gpu, &render_pipeline_layout, shader)
+            });
+
+        assets.insert(Image::new(UVec2::ONE, vec![255; 4]), "debug_draw_white");
+        let white_texture = assets.get::<Image>("debug_draw_white").unwrap();
+
+        gpu.create_texture(
+            white_texture.get_id(),
+            "Debug draw white texture",
+            &[255; 4],
+            1,
+            1,
+        );
+
+        let new_buffer = |label| {
+            BufferVec::new(&gpu.device, wgpu::BufferUsages::VERTEX, label, 1024)
+        };
+
+        let renderer_data = DebugDrawRendererData {
+            line_pipeline,
+            triangle_pipeline,
+            render_pipeline_layout,
+            shader,
+            lines: new_buffer("Debug line buffer"),
+            triangles: new_buffer("Debug triangle buffer"),
+            white_texture,
+            #[cfg(feature = "text")]
+            text_triangles: new_buffer("Debug text buffer"),
+            #[cfg(feature = "text")]
+            font_texture_id: None,
+        };
+
+        app.storage.singletons.insert(renderer_data);
+        app.storage.singletons.insert(DebugDraw::default());
+        app.storage.add_listener::<ShaderChanged>(on_shader_changed);
+
+        // Own pass after the main one so it's drawn on top of everything
+        app.render_graph
+            .add_node("debug_draw", Box::new(DebugDrawPlugin))
+            .reads(SURFACE_SLOT)
+            .writes(SURFACE_SLOT);
+
+        // First stage of the frame, everything after it can draw
+        app.schedular
+            .add_system(SystemStage::StateTransition, clear_debug_draw);
+        app.schedular
+            .add_system(SystemStage::Input, toggle_debug_draw);
+        app.schedular
+            .add_system(SystemStage::PreRender, prepare_debug_draw);
+        #[cfg(feature = "text")]
+        app.schedular
+            .add_system(SystemStage::PreRender, prepare_debug_text);
+    }
+}
+
+// Both or none, so a broken shader keeps the old pair
+fn create_pipelines(
+    gpu: &Gpu,
+    layout: &PipelineLayout,
+    shader: &Shader,
+) -> Option<(RenderPipeline, RenderPipeline)> {
+    let format = gpu.surface_config.format;
+
+    let line_pipeline = shader.create_pipeline(&gpu.device, SHADER_PATH, |module| {
+        create_pipeline(&gpu.device, module, layout, format, wgpu::PrimitiveTopology::LineList)
+    })?;
+
+    let triangle_pipeline = shader.create_pipeline(&gpu.device, SHADER_PATH, |module| {
+        create_pipeline(&gpu.device, module, layout, format, wgpu::PrimitiveTopology::TriangleList)
+    })?;
+
+    Some((line_pipeline, triangle_pipeline))
+}
+
+fn on_shader_changed(storage: &mut Storage, event: &ShaderChanged) {
+    let (gpu, data, assets) = storage
+        .singletons
+        .get_many_mut::<(Gpu, DebugDrawRendererData, AssetStorage)>()
+        .unwrap();
+
+    if event.shader.get_id() != data.shader.get_id() {
+        return;
+    }
+
+    let shader = assets.get_data(&data.shader);
+
+    if let Some((line_pipeline, triangle_pipeline)) =
+        create_pipelines(gpu, &data.render_pipeline_layout, shader)
+    {
+        data.line_pipeline = line_pipeline;
+        data.triangle_pipeline = triangle_pipeline;
+    }
+}
+
+fn create_pipeline(
+    device: &Device,
+    shader: &ShaderModule,
+    layout: &wgpu::PipelineLayout,
+    format: TextureFormat,
+    topology: wgpu::PrimitiveTopology,
+) -> RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Debug draw pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[DebugVertex::decs()],
+        },
+
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+
+        primitive: wgpu::PrimitiveState {
+            topology,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+
+        multiview: None,
+    })
+}
+
+fn clear_debug_draw(storage: &mut Storage) {
+    storage.singletons.get_mut::<DebugDraw>().unwrap().clear();
+}
+
+fn toggle_debug_draw(storage: &mut Storage) {
+    let (input, user_settings, debug_draw) = storage
+        .singletons
+        .get_many_mut::<(Input, UserSettings, DebugDraw)>()
+        .unwrap();
+
+    let pressed = matches!(
+        input.last_event,
+        Some(InputEvent::KeyboardEvent(KeyboardEvent::KeyEvent(KeyEvent {
+            button,
+            state: ButtonState::Pressed,
+        }))) if button == user_settings.key_bindings.debug_draw
+    );
+
+    if pressed {
+        debug_draw.toggle();
+    }
+}
+
+fn prepare_debug_draw(storage: &mut Storage) {
+    let (gpu, data, debug_draw) = storage
+        .singletons
+        .get_many_mut::<(Gpu, DebugDrawRendererData, DebugDraw)>()
+        .unwrap();
+
+    data.lines.clear();
+    data.triangles.clear();
+    data.lines.extend_from_slice(&debug_draw.lines);
+    data.triangles.extend_from_slice(&debug_draw.triangles);
+
+    data.lines.write_buffer(&gpu.device, &gpu.queue);
+    data.triangles.write_buffer(&gpu.device, &gpu.queue);
+}
+
+#[cfg(feature = "text")]
+fn prepare_debug_text(storage: &mut Storage) {
+    let (gpu, data, debug_draw, assets) = storage
+        .singletons
+        .get_many_mut::<(Gpu, DebugDrawRendererData, DebugDraw, AssetStorage)>()
+        .unwrap();
+
+    data.text_triangles.clear();
+    data.font_texture_id = None;
+
+    if let Some(font_ref) = &debug_draw.font {
+        let font_id = font_ref.get_id();
+        let font = assets.get_data(font_ref);
+
+        if !gpu.texture_bing_group_map.contains_key(&font_id) {
+            let size = font.atlas.get_size();
+            gpu.create_texture(font_id, "Font atlas", font.atlas.get_data(), size.x, size.y);
+        }
+
+        for (position, text) in debug_draw.texts.iter() {
+            for glyph in layout_text(text, font) {
+                let rect = Rect::from_center(glyph.size, glyph.center + *position);
+                let [a, b, c, d] = rect.get_corners();
+
+                // Atlas regions are top down, the bottom of the glyph gets the end of the region
+                let region = glyph.region;
+                let end = region.get_end();
+                let uvs = [
+                    [region.position.x, end.y],
+                    [end.x, end.y],
+                    [end.x, region.position.y],
+                    [region.position.x, region.position.y],
+                ];
+
+                let vertex = |point: Vec2, uv: [f32; 2]| DebugVertex {
+                    position: point.into(),
+                    uv,
+                    color: text.color.into(),
+                };
+
+                data.text_triangles.extend_from_slice(&[
+                    vertex(a, uvs[0]),
+                    vertex(b, uvs[1]),
+                    vertex(c, uvs[2]),
+                    vertex(a, uvs[0]),
+                    vertex(c, uvs[2]),
+                    vertex(d, uvs[3]),
+                ]);
+            }
+        }
+
+        data.font_texture_id = Some(font_id);
+    }
+
+    data.text_triangles.write_buffer(&gpu.device, &gpu.queue);
+}
+
+impl RenderNode for DebugDrawPlugin {
+    fn run(&self, context: &mut RenderContext, _world: &World, singletons: &Singletons) {
+        let (gpu, camera_data, data, debug_draw) = singletons
+            .get_many::<(Gpu, CameraBindGroup, DebugDrawRendererData, DebugDraw)>()
+            .unwrap();
+
+        if !debug_draw.enabled {
+            return;
+        }
+
+        // Not get_texture, the encoder is borrowed mutably below
+        let view = context.textures.get(SURFACE_SLOT).unwrap();
+
+        let mut render_pass = context.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("debug_draw"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        let white_texture = gpu
+            .texture_bing_group_map
+            .get(&data.white_texture.get_id())
+            .unwrap();
+
+        render_pass.set_bind_group(0, &camera_data.bind_group, &[]);
+        render_pass.set_bind_group(1, white_texture, &[]);
+
+        if !data.triangles.is_empty() {
+            render_pass.set_pipeline(&data.triangle_pipeline);
+            render_pass.set_vertex_buffer(0, data.triangles.buffer().slice(..));
+            render_pass.draw(0..data.triangles.len() as u32, 0..1);
+        }
+
+        if !data.lines.is_empty() {
+            render_pass.set_pipeline(&data.line_pipeline);
+            render_pass.set_vertex_buffer(0, data.lines.buffer().slice(..));
+            render_pass.draw(0..data.lines.len() as u32, 0..1);
+        }
+
+        #[cfg(feature = "text")]
+        if let Some(font_id) = data.font_texture_id {
+            if !data.text_triangles.is_empty() {
+                let font_texture = gpu.texture_bing_group_map.get(&font_id).unwrap();
+
+                render_pass.set_pipeline(&data.triangle_pipeline);
+                render_pass.set_bind_group(1, font_texture, &[]);
+                render_pass.set_vertex_buffer(0, data.text_triangles.buffer().slice(..));
+                render_pass.draw(0..data.text_triangles.len() as u32, 0..1);
+            }
+        }
+    }
+}
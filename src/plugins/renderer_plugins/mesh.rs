@@ -1,9 +1,7 @@
-use wgpu::Buffer;
 
-use crate::plugins::core::{asset_storage::Asset, render_plugin::Gpu};
+use crate::plugins::core::asset_storage::Asset;
 
 use super::vertex::Vertex;
-use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct Mesh {
@@ -44,11 +42,11 @@ impl Mesh {
                     color: [1.0; 3],
                 },
                 Vertex {
-                    position: [0.433012702, 0.25, 1.0],
+                    position: [0.433_012_7, 0.25, 1.0],
                     color: [1.0; 3],
                 },
                 Vertex {
-                    position: [0.433012702, -0.25, 1.0],
+                    position: [0.433_012_7, -0.25, 1.0],
                     color: [1.0; 3],
                 },
                 Vertex {
@@ -56,11 +54,11 @@ impl Mesh {
                     color: [1.0; 3],
                 },
                 Vertex {
-                    position: [-0.433012702, -0.25, 1.0],
+                    position: [-0.433_012_7, -0.25, 1.0],
                     color: [1.0; 3],
                 },
                 Vertex {
-                    position: [-0.433012702, 0.25, 1.0],
+                    position: [-0.433_012_7, 0.25, 1.0],
                     color: [1.0; 3],
                 },
             ]),
@@ -70,7 +68,7 @@ impl Mesh {
 }
 
 impl Asset for Mesh {
-    fn from_binary(binary: Vec<u8>) -> Self {
+    fn from_binary(_binary: Vec<u8>) -> Self {
         Mesh {
             vertices: Box::new([]),
             indices: Box::new([]),
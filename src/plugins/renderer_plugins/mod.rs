@@ -1,21 +1,27 @@
+use crate::app::PluginInfo;
+use crate::plugins::core::asset_storage::AssetStoragePlugin;
 use crate::app::Plugin;
 
 use self::{
+    debug_draw::DebugDrawPlugin,
     mesh::Mesh, 
     multi_instance_mesh_renderer::MultiInstanceMeshRenderer,
+    post_process::PostProcessPlugin,
+    sprite_animation::SpriteAnimationPlugin,
     sprite_renderer::SpritePlugin, 
     tilemap_renderer::TileMapRenderer,
 };
 
-use super::core::{
-    asset_storage::{self, AssetRef, AssetStorage},
-    camera_plugin::CameraPlugin,
-    render_plugin::RenderPlugin,
-};
+use super::core::asset_storage::AssetStorage;
 
+pub mod debug_draw;
 pub mod mesh;
 pub mod multi_instance_mesh_renderer;
+pub mod post_process;
+pub mod sprite_animation;
 pub mod sprite_renderer;
+#[cfg(feature = "text")]
+pub mod text;
 pub mod texture;
 pub mod tilemap_renderer;
 pub mod vertex;
@@ -23,10 +29,21 @@ pub mod vertex;
 pub struct Renderer2dPlugin;
 
 impl Plugin for Renderer2dPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<AssetStoragePlugin>(),
+        ]
+    }
+
     fn build(app: &mut crate::app::App) {
         app.register_plugin::<TileMapRenderer>();
         app.register_plugin::<SpritePlugin>();
+        app.register_plugin::<SpriteAnimationPlugin>();
+        #[cfg(feature = "text")]
+        app.register_plugin::<text::TextPlugin>();
         app.register_plugin::<MultiInstanceMeshRenderer>();
+        app.register_plugin::<DebugDrawPlugin>();
+        app.register_plugin::<PostProcessPlugin>();
 
         let asset_storage = app.storage.singletons.get_mut::<AssetStorage>().unwrap();
         asset_storage.insert(Mesh::get_hex_mesh(), "hex_mesh");
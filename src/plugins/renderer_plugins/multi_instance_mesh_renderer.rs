@@ -1,30 +1,48 @@
+use crate::plugins::core::render_graph::MAIN_PASS;
+use crate::app::PluginInfo;
+use crate::plugins::core::render_plugin::RenderPlugin;
+use crate::plugins::core::camera_plugin::CameraPlugin;
+use crate::plugins::core::buffer_vec::BufferVec;
+use crate::storage::Storage;
 use crate::{
     ecs::{
-        singletons::{self, Singletons},
+        singletons::Singletons,
         world::World, component::Component,
     },
     math::{
-        honeycomb::HEXAGON_INDICES,
-        transform2d::{self, Transform2d},
+        rect::Rect,
+        transform2d::Transform2d,
+    },
+    plugins::{
+        asset_types::shader::Shader,
+        core::{
+            asset_storage::{AssetRef, AssetStorage, AssetStoragePlugin},
+            camera_plugin::{CameraBindGroup, Viewport},
+            render_plugin::Renderer,
+            shader_reload::{ShaderChanged, ShaderReloadPlugin, ShaderWatcher},
+        },
     },
-    plugins::core::{camera_plugin::Camera, render_plugin::Renderer},
 };
 
-use glam::Mat3;
-use std::{
-    any::{Any, TypeId},
-    borrow::BorrowMut,
-    mem,
-    ops::Deref,
-    rc::Rc,
-    sync::Arc,
+use glam::{Mat3, Vec2};
+use std::sync::Arc;
+use wgpu::{
+    util::DeviceExt, BindGroupLayout, PipelineLayout, RenderPass, RenderPipeline, ShaderModule,
+    TextureFormat,
 };
-use wgpu::{include_wgsl, util::DeviceExt, BindGroupLayout, RenderPass, RenderPipeline};
 
 use crate::{app::Plugin, plugins::core::render_plugin::Gpu};
 
 use super::{mesh::Mesh, texture::Texture, vertex::Vertex};
 
+const SHADER_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/plugins/renderer_plugins/multi_instance_mesh.wgsl"
+);
+
+// Used when the file is missing or broken at startup
+const SHADER_SOURCE: &str = include_str!("multi_instance_mesh.wgsl");
+
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Debug)]
 pub struct InstanceData {
@@ -55,12 +73,13 @@ impl InstanceData {
     }
 }
 
-#[derive(Debug)]
 pub struct MultiInstanceMesh {
     pub instances: Vec<InstanceData>,
+    // Only the instances on screen, uploaded in PreRender
+    visible_instances: BufferVec<InstanceData>,
+    local_bounds: Rect,
     pub transform_buffer: wgpu::Buffer,
     pub bind_group: wgpu::BindGroup,
-    pub instance_data_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
     pub vertex_buffer: wgpu::Buffer,
     pub mesh: Arc<Mesh>,
@@ -84,36 +103,26 @@ impl MultiInstanceMesh {
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
 
-        let instance_data = InstanceData::new(&Transform2d::IDENTITY, [0.0; 3]);
-
-        let instance_data_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Instance Buffer"),
-            contents: bytemuck::cast_slice(&[instance_data; 4096]),
-            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-        });
-
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: None,
-            layout: &bind_group_layout.bind_group_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: transform_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: instance_data_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: wgpu::BindingResource::TextureView(&texture.view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 3,
-                    resource: wgpu::BindingResource::Sampler(&texture.sampler),
-                },
-            ],
-        });
+        let visible_instances = BufferVec::new(
+            device,
+            wgpu::BufferUsages::STORAGE,
+            "Instance Buffer",
+            64,
+        );
+
+        let bind_group = create_bind_group(
+            device,
+            bind_group_layout,
+            &transform_buffer,
+            visible_instances.buffer(),
+            &texture,
+        );
+
+        let local_bounds = Rect::from_points(
+            mesh.vertices
+                .iter()
+                .map(|vertex| Vec2::new(vertex.position[0], vertex.position[1])),
+        );
 
         let vertex_buffer = gpu
             .device
@@ -133,9 +142,10 @@ impl MultiInstanceMesh {
 
         Self {
             instances: Vec::new(),
+            visible_instances,
+            local_bounds,
             bind_group,
             transform_buffer,
-            instance_data_buffer,
             vertex_buffer,
             index_buffer,
             mesh,
@@ -144,10 +154,93 @@ impl MultiInstanceMesh {
     }
 }
 
+fn create_bind_group(
+    device: &wgpu::Device,
+    bind_group_layout: &MultiInstanceMeshBindGroupLayout,
+    transform_buffer: &wgpu::Buffer,
+    instance_data_buffer: &wgpu::Buffer,
+    texture: &Texture,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout: &bind_group_layout.bind_group_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: transform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: instance_data_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::TextureView(&texture.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::Sampler(&texture.sampler),
+            },
+        ],
+    })
+}
+
+// The visible rect comes from the Viewport, the same matrix the camera bind group draws with
+fn is_instance_visible(
+    instance: &InstanceData,
+    local_bounds: &Rect,
+    mesh_matrix: &Mat3,
+    visible_rect: &Rect,
+) -> bool {
+    let bounds = local_bounds.transformed(&(*mesh_matrix * instance.matrix));
+    visible_rect.intersects(&bounds)
+}
+
+fn prepare_instance_data(storage: &mut Storage) {
+    let (gpu, bind_group_layout, viewport) = storage
+        .singletons
+        .get_many::<(Gpu, MultiInstanceMeshBindGroupLayout, Viewport)>()
+        .unwrap();
+
+    let visible_rect = viewport.visible_world_rect();
+
+    for (multi_instance_mesh, transform2d) in storage
+        .world
+        .query_mut::<(MultiInstanceMesh, Transform2d)>()
+    {
+        let mesh_matrix = transform2d.create_matrix();
+        let MultiInstanceMesh {
+            instances,
+            visible_instances,
+            local_bounds,
+            ..
+        } = multi_instance_mesh;
+
+        visible_instances.clear();
+
+        for instance in instances.iter() {
+            if is_instance_visible(instance, local_bounds, &mesh_matrix, &visible_rect) {
+                visible_instances.push(*instance);
+            }
+        }
+
+        if visible_instances.write_buffer(&gpu.device, &gpu.queue) {
+            multi_instance_mesh.bind_group = create_bind_group(
+                &gpu.device,
+                bind_group_layout,
+                &multi_instance_mesh.transform_buffer,
+                multi_instance_mesh.visible_instances.buffer(),
+                &multi_instance_mesh.texture,
+            );
+        }
+    }
+}
+
 pub struct MultiInstanceMeshRendererData {
     render_pipeline: RenderPipeline,
-    camera_buffer: wgpu::Buffer,
-    camera_bind_group: wgpu::BindGroup,
+    // Kept around to rebuild the pipeline when the shader changes
+    render_pipeline_layout: PipelineLayout,
+    shader: AssetRef<Shader>,
 }
 
 pub struct MultiInstanceMeshRenderer;
@@ -162,15 +255,10 @@ impl Renderer for MultiInstanceMeshRenderer {
         
         let data = singletons.get::<MultiInstanceMeshRendererData>().unwrap();
         let gpu = singletons.get::<Gpu>().unwrap();
-
-        let (camera, transform2d) = world.query_single::<(Camera, Transform2d)>();
-        let projection = transform2d.create_matrix() * camera.projection;
+        let camera_data = singletons.get::<CameraBindGroup>().unwrap();
 
         render_pass.set_pipeline(&data.render_pipeline);
-
-        gpu.queue
-            .write_buffer(&data.camera_buffer, 0, bytemuck::cast_slice(&[projection]));
-        render_pass.set_bind_group(1, &data.camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &camera_data.bind_group, &[]);
 
         for (multi_instance_mesh, transform2d) in world.query::<(MultiInstanceMesh, Transform2d)>()
         {
@@ -186,18 +274,12 @@ impl Renderer for MultiInstanceMeshRenderer {
                 bytemuck::cast_slice(&[transform2d.create_matrix()]),
             );
 
-            gpu.queue.write_buffer(
-                &multi_instance_mesh.instance_data_buffer,
-                0,
-                bytemuck::cast_slice(&multi_instance_mesh.instances), // Should I cache it??
-            );
-
             render_pass.set_bind_group(0, &multi_instance_mesh.bind_group, &[]);
 
             render_pass.draw_indexed(
                 0..multi_instance_mesh.mesh.indices.len() as u32,
                 0,
-                0..multi_instance_mesh.instances.len() as u32,
+                0..multi_instance_mesh.visible_instances.len() as u32,
             );
         }
     }
@@ -208,11 +290,23 @@ pub struct MultiInstanceMeshBindGroupLayout {
 }
 
 impl Plugin for MultiInstanceMeshRenderer {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<RenderPlugin>(),
+            PluginInfo::of::<CameraPlugin>(),
+            PluginInfo::of::<AssetStoragePlugin>(),
+            PluginInfo::of::<ShaderReloadPlugin>(),
+        ]
+    }
+
     fn build(app: &mut crate::app::App) {
-        let gpu = app.storage.singletons.get::<Gpu>().unwrap();
-        let shader = gpu
-            .device
-            .create_shader_module(include_wgsl!("multi_instance_mesh.wgsl"));
+        let (gpu, camera_bind_group, assets, shader_watcher) = app
+            .storage
+            .singletons
+            .get_many_mut::<(Gpu, CameraBindGroup, AssetStorage, ShaderWatcher)>()
+            .unwrap();
+
+        let shader = shader_watcher.load(assets, SHADER_PATH, SHADER_SOURCE);
 
         let bind_group_layout =
             gpu.device
@@ -260,101 +354,163 @@ impl Plugin for MultiInstanceMeshRenderer {
                     ],
                 });
 
-        let camera_bind_group_layout =
-            gpu.device
-                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                    label: Some("Camera bind group layout"),
-                    entries: &[wgpu::BindGroupLayoutEntry {
-                        binding: 0,
-                        visibility: wgpu::ShaderStages::VERTEX,
-                        ty: wgpu::BindingType::Buffer {
-                            ty: wgpu::BufferBindingType::Uniform,
-                            has_dynamic_offset: false,
-                            min_binding_size: None,
-                        },
-                        count: None,
-                    }],
-                });
-
-        let camera_buffer = gpu
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Camera buffer"),
-                contents: bytemuck::cast_slice(&[Mat3::IDENTITY]),
-                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            });
-
-        let camera_bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Camera bind group"),
-            layout: &camera_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer.as_entire_binding(),
-            }],
-        });
-
         let render_pipeline_layout =
             gpu.device
                 .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                     label: Some("Render Pipeline Layout"),
-                    bind_group_layouts: &[&bind_group_layout, &camera_bind_group_layout],
+                    bind_group_layouts: &[&bind_group_layout, &camera_bind_group.layout],
                     push_constant_ranges: &[],
                 });
 
-        let render_pipeline = gpu
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Render Pipeline"),
-                layout: Some(&render_pipeline_layout),
-
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: "vs_main",
-                    buffers: &[Vertex::decs()],
-                },
-
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: "fs_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: gpu.surface_config.format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: Some(wgpu::Face::Back),
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
-                },
-
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-            });
+        let render_pipeline =
+            assets
+                .get_data(&shader)
+                .build_with_fallback(SHADER_PATH, SHADER_SOURCE, |shader| {
+                    shader.create_pipeline(&gpu.device, SHADER_PATH, |module| {
+                        create_render_pipeline(
+                            &gpu.device,
+                            &render_pipeline_layout,
+                            gpu.surface_config.format,
+                            module,
+                        )
+                    })
+                });
 
         let tile_map_data = MultiInstanceMeshRendererData {
             render_pipeline,
-            camera_buffer,
-            camera_bind_group,
+            render_pipeline_layout,
+            shader,
         };
 
         let tile_map_bind_group_layout = MultiInstanceMeshBindGroupLayout { bind_group_layout };
 
         app.storage.world.register_component::<MultiInstanceMesh>();
+        app.storage.add_listener::<ShaderChanged>(on_shader_changed);
         app.storage.singletons.insert(tile_map_bind_group_layout);
 
-        app.renderers.push(Box::new(MultiInstanceMeshRenderer {}));
+        app.render_graph.add_to_pass(MAIN_PASS, Box::new(MultiInstanceMeshRenderer {}));
 
         app.storage.singletons.insert(tile_map_data);
+        app.schedular
+            .add_system(crate::app::SystemStage::PreRender, prepare_instance_data);
+    }
+}
+
+fn create_render_pipeline(
+    device: &wgpu::Device,
+    layout: &PipelineLayout,
+    format: TextureFormat,
+    module: &ShaderModule,
+) -> RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(layout),
+
+        vertex: wgpu::VertexState {
+            module,
+            entry_point: "vs_main",
+            buffers: &[Vertex::decs()],
+        },
+
+        fragment: Some(wgpu::FragmentState {
+            module,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    })
+}
+
+// Keeps the old pipeline if the new source is broken
+fn on_shader_changed(storage: &mut Storage, event: &ShaderChanged) {
+    let (gpu, data, assets) = storage
+        .singletons
+        .get_many_mut::<(Gpu, MultiInstanceMeshRendererData, AssetStorage)>()
+        .unwrap();
+
+    if event.shader.get_id() != data.shader.get_id() {
+        return;
+    }
+
+    let pipeline = assets
+        .get_data(&data.shader)
+        .create_pipeline(&gpu.device, &event.path, |module| {
+            create_render_pipeline(
+                &gpu.device,
+                &data.render_pipeline_layout,
+                gpu.surface_config.format,
+                module,
+            )
+        });
+
+    if let Some(pipeline) = pipeline {
+        data.render_pipeline = pipeline;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 800x600 window, camera zoomed in 2x and looking at x = 1000.
+    // Same matrix on_update puts in the Viewport and the camera bind group draws with.
+    fn visible_rect() -> Rect {
+        let projection = Mat3::from_scale(Vec2::new(2.0 / 800.0, 2.0 / 600.0));
+        let camera = Transform2d::new(Vec2::new(-2000.0, 0.0), 0.0, Vec2::splat(2.0));
+
+        let mut viewport = Viewport::default();
+        viewport.set_projection_view(projection * camera.create_matrix());
+        viewport.visible_world_rect()
+    }
+
+    fn is_visible(position: Vec2) -> bool {
+        let instance = InstanceData::new(&Transform2d::new(position, 0.0, Vec2::ONE), [1.0; 3]);
+        let local_bounds = Rect::from_center(Vec2::splat(10.0), Vec2::ZERO);
+
+        is_instance_visible(&instance, &local_bounds, &Mat3::IDENTITY, &visible_rect())
+    }
+
+    #[test]
+    fn visible_rect_follows_the_camera() {
+        let rect = visible_rect();
+
+        assert!(rect.get_center().abs_diff_eq(Vec2::new(1000.0, 0.0), 1e-3));
+        assert!(rect.size.abs_diff_eq(Vec2::new(400.0, 300.0), 1e-3));
+    }
+
+    #[test]
+    fn instances_in_front_of_a_moved_camera_are_kept() {
+        assert!(is_visible(Vec2::new(1000.0, 0.0)));
+        // Right edge at 1195, still inside 800..1200
+        assert!(is_visible(Vec2::new(1190.0, 140.0)));
+    }
+
+    #[test]
+    fn instances_the_moved_camera_left_behind_are_culled() {
+        // In view of a camera at the origin, not of this one
+        assert!(!is_visible(Vec2::ZERO));
+        // Left edge at 1201
+        assert!(!is_visible(Vec2::new(1206.0, 0.0)));
     }
 }
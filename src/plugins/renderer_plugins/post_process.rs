@@ -0,0 +1,736 @@
+use bytemuck::{Pod, Zeroable};
+use hashbrown::HashMap;
+use wgpu::{BindGroupLayout, Device, PipelineLayout, RenderPipeline, TextureFormat};
+
+use crate::{
+    app::{Plugin, PluginInfo, SystemStage},
+    ecs::{
+        component::Component,
+        singletons::Singletons,
+        world::{QuerySingleError, World},
+    },
+    math::{color::Color, rect::Rect},
+    plugins::{
+        asset_types::{image::Image, shader::Shader},
+        core::{
+            asset_storage::{AssetRef, AssetStorage, AssetStoragePlugin},
+            buffer_vec::BufferVec,
+            camera_plugin::{Camera, CameraPlugin, Viewport},
+            render_graph::{RenderContext, RenderNode, MAIN_PASS, SURFACE_SLOT},
+            render_plugin::{Gpu, RenderPlugin},
+            shader_reload::{ShaderChanged, ShaderReloadPlugin, ShaderWatcher},
+        },
+    },
+    storage::Storage,
+};
+
+// The main pass renders in here instead of the surface when post processing is on
+pub const SCENE_SLOT: &str = "scene";
+
+const SHADER_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/plugins/renderer_plugins/post_process.wgsl"
+);
+
+// Used when the file is missing or broken at startup
+const SHADER_SOURCE: &str = include_str!("post_process.wgsl");
+
+// fs_lut is left out, it has its own layout
+const EFFECT_ENTRY_POINTS: [&str; 5] = [
+    "fs_copy",
+    "fs_color_grading",
+    "fs_vignette",
+    "fs_bloom",
+    "fs_desaturate",
+];
+
+// Regions past this are ignored, they have to fit in the 256 bytes of effect params
+pub const MAX_DESATURATE_REGIONS: usize = 15;
+
+// Exposure in stops, contrast and saturation are 1.0 for no change
+#[derive(Clone, Copy)]
+pub struct ColorGrading {
+    pub exposure: f32,
+    pub contrast: f32,
+    pub saturation: f32,
+    pub tint: Color,
+}
+
+impl Default for ColorGrading {
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            contrast: 1.0,
+            saturation: 1.0,
+            tint: Color::WHITE,
+        }
+    }
+}
+
+// A strip of size x size squares, e.g. 256x16. Red goes right, green down and blue picks the square.
+// Colors in the image are srgb, same as what an image editor shows.
+#[derive(Clone)]
+pub struct Lut {
+    pub image: AssetRef<Image>,
+    pub strength: f32,
+}
+
+impl Lut {
+    pub fn new(image: AssetRef<Image>) -> Self {
+        Self {
+            image,
+            strength: 1.0,
+        }
+    }
+}
+
+// Radius and smoothness are in uv distance from the center, 1.0 is the corner
+#[derive(Clone, Copy)]
+pub struct Vignette {
+    pub intensity: f32,
+    pub radius: f32,
+    pub smoothness: f32,
+    pub color: Color,
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Self {
+            intensity: 0.5,
+            radius: 1.0,
+            smoothness: 0.6,
+            color: Color::new(0.0, 0.0, 0.0, 1.0),
+        }
+    }
+}
+
+// Radius is in pixels
+#[derive(Clone, Copy)]
+pub struct Bloom {
+    pub threshold: f32,
+    pub intensity: f32,
+    pub radius: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Self {
+            threshold: 0.8,
+            intensity: 0.6,
+            radius: 4.0,
+        }
+    }
+}
+
+// Greys out the regions, e.g. parts of the map that are not unlocked yet.
+// Regions are in world space and go through the Viewport, no regions means the whole screen.
+#[derive(Clone)]
+pub struct Desaturate {
+    pub amount: f32,
+    pub regions: Vec<Rect>,
+}
+
+impl Default for Desaturate {
+    fn default() -> Self {
+        Self {
+            amount: 1.0,
+            regions: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum PostEffect {
+    ColorGrading(ColorGrading),
+    Lut(Lut),
+    Vignette(Vignette),
+    Bloom(Bloom),
+    Desaturate(Desaturate),
+}
+
+impl PostEffect {
+    fn entry_point(&self) -> &'static str {
+        match self {
+            PostEffect::ColorGrading(_) => "fs_color_grading",
+            PostEffect::Lut(_) => "fs_lut",
+            PostEffect::Vignette(_) => "fs_vignette",
+            PostEffect::Bloom(_) => "fs_bloom",
+            PostEffect::Desaturate(_) => "fs_desaturate",
+        }
+    }
+
+    // Has to match what post_process.wgsl reads for the effect
+    fn get_params(&self, viewport: &Viewport, assets: &AssetStorage) -> EffectParams {
+        let mut params = EffectParams::default();
+
+        match self {
+            PostEffect::ColorGrading(grading) => {
+                params.values[0] = [grading.exposure, grading.contrast, grading.saturation, 0.0];
+                params.values[1] = grading.tint.into();
+            }
+            PostEffect::Lut(lut) => {
+                let size = assets.get_data(&lut.image).get_size().y;
+                params.values[0] = [lut.strength, size as f32, 0.0, 0.0];
+            }
+            PostEffect::Vignette(vignette) => {
+                params.values[0] = [vignette.intensity, vignette.radius, vignette.smoothness, 0.0];
+                params.values[1] = vignette.color.into();
+            }
+            PostEffect::Bloom(bloom) => {
+                params.values[0] = [bloom.threshold, bloom.intensity, bloom.radius, 0.0];
+            }
+            PostEffect::Desaturate(desaturate) => {
+                let regions: Vec<&Rect> =
+                    desaturate.regions.iter().take(MAX_DESATURATE_REGIONS).collect();
+
+                params.values[0] = [desaturate.amount, regions.len() as f32, 0.0, 0.0];
+
+                // Clip space has y going up, uvs go down
+                for (index, region) in regions.iter().enumerate() {
+                    let clip = region.transformed(viewport.get_world_to_screen_mat());
+                    let end = clip.get_end();
+
+                    params.values[index + 1] = [
+                        (clip.position.x + 1.0) * 0.5,
+                        (1.0 - end.y) * 0.5,
+                        (end.x + 1.0) * 0.5,
+                        (1.0 - clip.position.y) * 0.5,
+                    ];
+                }
+            }
+        }
+
+        params
+    }
+}
+
+#[derive(Clone)]
+struct PostEffectEntry {
+    name: String,
+    enabled: bool,
+    effect: PostEffect,
+}
+
+// Put it on the camera. Effects run in the order they were added, each one reads what the one
+// before it wrote. Everything is rendered through one camera (the Viewport follows it), so only one
+// PostProcess is supported, with more than one post processing is off and an error is logged.
+#[derive(Clone)]
+pub struct PostProcess {
+    pub enabled: bool,
+    effects: Vec<PostEffectEntry>,
+}
+
+impl Component for PostProcess {}
+
+impl Default for PostProcess {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PostProcess {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            effects: Vec::new(),
+        }
+    }
+
+    pub fn with_effect(mut self, name: &str, effect: PostEffect) -> Self {
+        self.add_effect(name, effect);
+        self
+    }
+
+    pub fn add_effect(&mut self, name: &str, effect: PostEffect) {
+        self.effects.push(PostEffectEntry {
+            name: name.to_string(),
+            enabled: true,
+            effect,
+        });
+    }
+
+    pub fn remove_effect(&mut self, name: &str) -> Option<PostEffect> {
+        let index = self.effects.iter().position(|entry| entry.name == name)?;
+        Some(self.effects.remove(index).effect)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PostEffect> {
+        self.effects
+            .iter()
+            .find(|entry| entry.name == name)
+            .map(|entry| &entry.effect)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut PostEffect> {
+        self.effects
+            .iter_mut()
+            .find(|entry| entry.name == name)
+            .map(|entry| &mut entry.effect)
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.effects
+            .iter()
+            .any(|entry| entry.name == name && entry.enabled)
+    }
+
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) {
+        for entry in self.effects.iter_mut().filter(|entry| entry.name == name) {
+            entry.enabled = enabled;
+        }
+    }
+
+    pub fn toggle(&mut self, name: &str) {
+        let enabled = self.is_enabled(name);
+        self.set_enabled(name, !enabled);
+    }
+}
+
+// 256 bytes so every effect's params start at a valid dynamic offset
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable, Default)]
+struct EffectParams {
+    values: [[f32; 4]; 16],
+}
+
+const PARAMS_SIZE: u64 = std::mem::size_of::<EffectParams>() as u64;
+
+struct PreparedEffect {
+    entry_point: &'static str,
+    params_offset: u32,
+    lut: Option<u64>,
+}
+
+// Effects ping pong between these two, the last one writes into the surface
+struct ColorTarget {
+    texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+}
+
+struct PostProcessData {
+    pipelines: HashMap<&'static str, RenderPipeline>,
+    effect_layout: PipelineLayout,
+    lut_layout: PipelineLayout,
+    shader: AssetRef<Shader>,
+    targets: [ColorTarget; 2],
+    sampler: wgpu::Sampler,
+
+    params: BufferVec<EffectParams>,
+    params_bind_group: wgpu::BindGroup,
+    params_bind_group_layout: BindGroupLayout,
+
+    effects: Vec<PreparedEffect>,
+}
+
+pub struct PostProcessPlugin;
+
+// Hands the intermediate texture to the main pass
+struct SceneTargetNode;
+
+impl Plugin for PostProcessPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<RenderPlugin>(),
+            PluginInfo::of::<CameraPlugin>(),
+            PluginInfo::of::<AssetStoragePlugin>(),
+            PluginInfo::of::<ShaderReloadPlugin>(),
+        ]
+    }
+
+    fn build(app: &mut crate::app::App) {
+        app.storage.world.register_component::<PostProcess>();
+
+        let (gpu, assets, shader_watcher) = app
+            .storage
+            .singletons
+            .get_many_mut::<(Gpu, AssetStorage, ShaderWatcher)>()
+            .unwrap();
+
+        let shader = shader_watcher.load(assets, SHADER_PATH, SHADER_SOURCE);
+
+        let params_bind_group_layout =
+            gpu.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Post process params bind group layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: true,
+                            min_binding_size: wgpu::BufferSize::new(PARAMS_SIZE),
+                        },
+                        count: None,
+                    }],
+                });
+
+        let effect_layout = gpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Post process pipeline layout"),
+                bind_group_layouts: &[&gpu.texture_bind_group_layout, &params_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        // The lut is one more texture
+        let lut_layout = gpu
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Post process lut pipeline layout"),
+                bind_group_layouts: &[
+                    &gpu.texture_bind_group_layout,
+                    &params_bind_group_layout,
+                    &gpu.texture_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+
+        let pipelines = assets
+            .get_data(&shader)
+            .build_with_fallback(SHADER_PATH, SHADER_SOURCE, |shader| {
+                create_pipelines(gpu, &effect_layout, &lut_layout, shader)
+            });
+
+        let sampler = gpu.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post process sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let targets = [
+            create_color_target(gpu, &sampler),
+            create_color_target(gpu, &sampler),
+        ];
+
+        let params = BufferVec::new(
+            &gpu.device,
+            wgpu::BufferUsages::UNIFORM,
+            "Post process params buffer",
+            8,
+        );
+
+        let params_bind_group =
+            create_params_bind_group(&gpu.device, &params_bind_group_layout, params.buffer());
+
+        let data = PostProcessData {
+            pipelines,
+            effect_layout,
+            lut_layout,
+            shader,
+            targets,
+            sampler,
+            params,
+            params_bind_group,
+            params_bind_group_layout,
+            effects: Vec::new(),
+        };
+
+        app.storage.singletons.insert(data);
+        app.storage.add_listener::<ShaderChanged>(on_shader_changed);
+
+        // Main pass -> scene texture -> effects -> surface, anything drawing on the surface after
+        // this (like debug draw) is not post processed
+        app.render_graph
+            .add_node("post_process_scene", Box::new(SceneTargetNode))
+            .writes(SCENE_SLOT);
+        app.render_graph.set_pass_target(MAIN_PASS, SCENE_SLOT);
+        app.render_graph
+            .get_node_mut(MAIN_PASS)
+            .unwrap()
+            .after("post_process_scene");
+        app.render_graph
+            .add_node("post_process", Box::new(PostProcessPlugin))
+            .reads(SCENE_SLOT)
+            .writes(SURFACE_SLOT);
+
+        app.schedular
+            .add_system(SystemStage::PreRender, prepare_post_process);
+    }
+}
+
+// All or none, so a broken shader keeps the old pipelines
+fn create_pipelines(
+    gpu: &Gpu,
+    effect_layout: &PipelineLayout,
+    lut_layout: &PipelineLayout,
+    shader: &Shader,
+) -> Option<HashMap<&'static str, RenderPipeline>> {
+    let format = gpu.surface_config.format;
+    let mut pipelines = HashMap::new();
+
+    let layouts = EFFECT_ENTRY_POINTS
+        .iter()
+        .map(|entry_point| (*entry_point, effect_layout))
+        .chain([("fs_lut", lut_layout)]);
+
+    for (entry_point, layout) in layouts {
+        let pipeline = shader.create_pipeline(&gpu.device, SHADER_PATH, |module| {
+            create_pipeline(&gpu.device, module, layout, format, entry_point)
+        })?;
+
+        pipelines.insert(entry_point, pipeline);
+    }
+
+    Some(pipelines)
+}
+
+fn on_shader_changed(storage: &mut Storage, event: &ShaderChanged) {
+    let (gpu, data, assets) = storage
+        .singletons
+        .get_many_mut::<(Gpu, PostProcessData, AssetStorage)>()
+        .unwrap();
+
+    if event.shader.get_id() != data.shader.get_id() {
+        return;
+    }
+
+    let shader = assets.get_data(&data.shader);
+
+    if let Some(pipelines) = create_pipelines(gpu, &data.effect_layout, &data.lut_layout, shader) {
+        data.pipelines = pipelines;
+    }
+}
+
+fn create_pipeline(
+    device: &Device,
+    shader: &wgpu::ShaderModule,
+    layout: &wgpu::PipelineLayout,
+    format: TextureFormat,
+    entry_point: &str,
+) -> RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(entry_point),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point,
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+
+        multiview: None,
+    })
+}
+
+// Same size and format as the surface so every renderer's pipeline can draw into it
+fn create_color_target(gpu: &Gpu, sampler: &wgpu::Sampler) -> ColorTarget {
+    let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Post process texture"),
+        size: wgpu::Extent3d {
+            width: gpu.surface_config.width.max(1),
+            height: gpu.surface_config.height.max(1),
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: gpu.surface_config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Post process texture bind group"),
+        layout: &gpu.texture_bind_group_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+        ],
+    });
+
+    ColorTarget {
+        texture,
+        bind_group,
+    }
+}
+
+fn create_params_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Post process params bind group"),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer,
+                offset: 0,
+                size: wgpu::BufferSize::new(PARAMS_SIZE),
+            }),
+        }],
+    })
+}
+
+fn prepare_post_process(storage: &mut Storage) {
+    let (gpu, data, viewport, assets) = storage
+        .singletons
+        .get_many_mut::<(Gpu, PostProcessData, Viewport, AssetStorage)>()
+        .unwrap();
+
+    // Cheaper than listening to Resize, works for offscreen targets too
+    let texture = &data.targets[0].texture;
+    if texture.width() != gpu.surface_config.width || texture.height() != gpu.surface_config.height
+    {
+        data.targets = [
+            create_color_target(gpu, &data.sampler),
+            create_color_target(gpu, &data.sampler),
+        ];
+    }
+
+    data.params.clear();
+    data.effects.clear();
+
+    let post_process = match storage.world.query_single::<(Camera, PostProcess)>() {
+        Ok((_, post_process)) => Some(post_process).filter(|post_process| post_process.enabled),
+        Err(QuerySingleError::NoMatch { .. }) => None,
+        Err(err) => {
+            log::error!("Post processing only supports one camera: {err}");
+            None
+        }
+    };
+
+    let enabled_effects = post_process
+        .iter()
+        .flat_map(|post_process| post_process.effects.iter())
+        .filter(|entry| entry.enabled);
+
+    for entry in enabled_effects {
+        let mut lut = None;
+
+        if let PostEffect::Lut(lut_effect) = &entry.effect {
+            let id = lut_effect.image.get_id();
+
+            if !gpu.texture_bing_group_map.contains_key(&id) {
+                let image = assets.get_data(&lut_effect.image);
+                let size = image.get_size();
+                gpu.create_texture(id, "Lut", image.get_data(), size.x, size.y);
+            }
+
+            lut = Some(id);
+        }
+
+        let index = data.params.push(entry.effect.get_params(viewport, assets));
+
+        data.effects.push(PreparedEffect {
+            entry_point: entry.effect.entry_point(),
+            params_offset: index as u32 * PARAMS_SIZE as u32,
+            lut,
+        });
+    }
+
+    // Still has to get from the scene texture to the surface
+    if data.effects.is_empty() {
+        data.params.push(EffectParams::default());
+        data.effects.push(PreparedEffect {
+            entry_point: "fs_copy",
+            params_offset: 0,
+            lut: None,
+        });
+    }
+
+    if data.params.write_buffer(&gpu.device, &gpu.queue) {
+        data.params_bind_group = create_params_bind_group(
+            &gpu.device,
+            &data.params_bind_group_layout,
+            data.params.buffer(),
+        );
+    }
+}
+
+impl RenderNode for SceneTargetNode {
+    fn run(&self, context: &mut RenderContext, _world: &World, singletons: &Singletons) {
+        let data = singletons.get::<PostProcessData>().unwrap();
+
+        let view = data.targets[0]
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        context.textures.insert(SCENE_SLOT.to_string(), view);
+    }
+}
+
+impl RenderNode for PostProcessPlugin {
+    fn run(&self, context: &mut RenderContext, _world: &World, singletons: &Singletons) {
+        let (gpu, data) = singletons.get_many::<(Gpu, PostProcessData)>().unwrap();
+
+        // The scene is in targets[0], every effect reads one target and writes the other
+        for (index, effect) in data.effects.iter().enumerate() {
+            let input = &data.targets[index % 2];
+            let output = &data.targets[(index + 1) % 2];
+
+            let output_view;
+            let view = if index == data.effects.len() - 1 {
+                context.textures.get(SURFACE_SLOT).unwrap()
+            } else {
+                output_view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+                &output_view
+            };
+
+            let mut render_pass = context.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some(effect.entry_point),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_pipeline(&data.pipelines[effect.entry_point]);
+            render_pass.set_bind_group(0, &input.bind_group, &[]);
+            render_pass.set_bind_group(1, &data.params_bind_group, &[effect.params_offset]);
+
+            if let Some(lut) = effect.lut {
+                let lut_texture = gpu.texture_bing_group_map.get(&lut).unwrap();
+                render_pass.set_bind_group(2, lut_texture, &[]);
+            }
+
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// Every effect gets the same 256 bytes, what is in them depends on the effect
+struct EffectParams {
+    values: array<vec4<f32>, 16>,
+}
+
+@group(0) @binding(0) var input: texture_2d<f32>;
+@group(0) @binding(1) var input_sampler: sampler;
+@group(1) @binding(0) var<uniform> params: EffectParams;
+@group(2) @binding(0) var lut: texture_2d<f32>;
+@group(2) @binding(1) var lut_sampler: sampler;
+
+// One triangle covering the whole screen, no vertex buffer needed
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+
+    let position = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    out.clip_position = vec4<f32>(position * 2.0 - 1.0, 0.0, 1.0);
+    // Textures start at the top left
+    out.uv = vec2<f32>(position.x, 1.0 - position.y);
+
+    return out;
+}
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+fn to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let low = color * 12.92;
+    let high = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(high, low, color <= vec3<f32>(0.0031308));
+}
+
+@fragment
+fn fs_copy(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(input, input_sampler, in.uv);
+}
+
+// 0: exposure, contrast, saturation
+// 1: tint
+@fragment
+fn fs_color_grading(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input, input_sampler, in.uv);
+    let settings = params.values[0];
+
+    var rgb = color.rgb * exp2(settings.x);
+    rgb = (rgb - 0.5) * settings.y + 0.5;
+    rgb = mix(vec3<f32>(luminance(rgb)), rgb, settings.z);
+    rgb = rgb * params.values[1].rgb;
+
+    return vec4<f32>(max(rgb, vec3<f32>(0.0)), color.a);
+}
+
+// 0: strength, lut size
+// The lut is a strip of `size` squares of size x size, red goes right, green goes down and blue
+// picks the square
+@fragment
+fn fs_lut(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input, input_sampler, in.uv);
+    let strength = params.values[0].x;
+    let size = params.values[0].y;
+
+    let cell = clamp(to_srgb(color.rgb), vec3<f32>(0.0), vec3<f32>(1.0)) * (size - 1.0);
+    let blue = floor(cell.b);
+    let next_blue = min(blue + 1.0, size - 1.0);
+
+    let uv = vec2<f32>(cell.r + 0.5, cell.g + 0.5) / vec2<f32>(size * size, size);
+    let first = textureSample(lut, lut_sampler, uv + vec2<f32>(blue / size, 0.0)).rgb;
+    let second = textureSample(lut, lut_sampler, uv + vec2<f32>(next_blue / size, 0.0)).rgb;
+
+    let graded = mix(first, second, cell.b - blue);
+
+    return vec4<f32>(mix(color.rgb, graded, strength), color.a);
+}
+
+// 0: intensity, radius, smoothness
+// 1: color
+@fragment
+fn fs_vignette(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input, input_sampler, in.uv);
+    let settings = params.values[0];
+
+    let distance = length(in.uv - 0.5) * 1.41421356;
+    let amount = smoothstep(settings.y - settings.z, settings.y, distance) * settings.x;
+
+    return vec4<f32>(mix(color.rgb, params.values[1].rgb, amount), color.a);
+}
+
+// 0: threshold, intensity, radius in pixels
+// Single pass, blurs the bright parts with three rings of samples around the pixel
+@fragment
+fn fs_bloom(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input, input_sampler, in.uv);
+    let settings = params.values[0];
+
+    let texel = settings.z / vec2<f32>(textureDimensions(input));
+
+    var glow = vec3<f32>(0.0);
+    var total = 0.0;
+
+    for (var ring = 1; ring <= 3; ring++) {
+        let weight = 1.0 / f32(ring);
+
+        for (var i = 0; i < 8; i++) {
+            let angle = f32(i) * 0.78539816 + f32(ring) * 0.3;
+            let offset = vec2<f32>(cos(angle), sin(angle)) * texel * f32(ring);
+            let sample = textureSample(input, input_sampler, in.uv + offset).rgb;
+
+            glow += max(sample - vec3<f32>(settings.x), vec3<f32>(0.0)) * weight;
+            total += weight;
+        }
+    }
+
+    return vec4<f32>(color.rgb + glow / total * settings.y, color.a);
+}
+
+// 0: amount, region count
+// 1..: regions as min xy and max xy in uv space, no regions means the whole screen
+@fragment
+fn fs_desaturate(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input, input_sampler, in.uv);
+    let amount = params.values[0].x;
+    let region_count = i32(params.values[0].y);
+
+    var inside = region_count == 0;
+
+    for (var i = 0; i < region_count; i++) {
+        let region = params.values[i + 1];
+
+        if all(in.uv >= region.xy) && all(in.uv <= region.zw) {
+            inside = true;
+        }
+    }
+
+    if !inside {
+        return color;
+    }
+
+    return vec4<f32>(mix(color.rgb, vec3<f32>(luminance(color.rgb)), amount), color.a);
+}
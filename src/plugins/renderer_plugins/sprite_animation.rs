@@ -0,0 +1,359 @@
+use hashbrown::HashMap;
+
+use crate::{
+    app::{Plugin, PluginInfo, SystemStage},
+    ecs::{component::Component, entity::EntityId, event_bus::WorldEvent},
+    math::rect::Rect,
+    plugins::{
+        asset_types::sprite_sheet::SpriteSheet,
+        core::timer_plugin::{Time, TimerPlugin},
+    },
+    storage::Storage,
+};
+
+use super::sprite_renderer::{Sprite, SpritePlugin};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationMode {
+    Loop,
+    // 0 1 2 1 0 1 2 ...
+    PingPong,
+    // Stops on the last frame and emits AnimationFinished
+    Once,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AnimationFrame {
+    pub region: Rect, // uv rect, same as Sprite::region
+    pub duration: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationClip {
+    pub frames: Vec<AnimationFrame>,
+    pub mode: AnimationMode,
+}
+
+impl AnimationClip {
+    pub fn new(mode: AnimationMode) -> Self {
+        Self {
+            frames: Vec::new(),
+            mode,
+        }
+    }
+
+    // Grid frames of the sheet, all with the same duration
+    pub fn from_sheet(
+        sheet: &SpriteSheet,
+        frames: impl IntoIterator<Item = usize>,
+        frame_duration: f32,
+        mode: AnimationMode,
+    ) -> Self {
+        let frames = frames
+            .into_iter()
+            .map(|index| AnimationFrame {
+                region: sheet
+                    .get_frame(index)
+                    .unwrap_or_else(|| panic!("Sprite sheet has no frame {index}")),
+                duration: frame_duration,
+            })
+            .collect();
+
+        Self { frames, mode }
+    }
+
+    pub fn with_frame(mut self, region: Rect, duration: f32) -> Self {
+        self.frames.push(AnimationFrame { region, duration });
+        self
+    }
+}
+
+// Emitted once when a clip in Once mode reaches the end of its last frame
+pub struct AnimationFinished {
+    pub entity: EntityId,
+    pub clip: String,
+}
+
+impl WorldEvent for AnimationFinished {}
+
+// Needs a Sprite on the same entity, its region gets replaced with the current frame
+pub struct SpriteAnimation {
+    clips: HashMap<String, AnimationClip>,
+    current: Option<String>,
+    frame: usize,
+    elapsed: f32,
+    backwards: bool,
+    playing: bool,
+    // Never negative, see set_speed
+    speed: f32,
+}
+
+impl Component for SpriteAnimation {}
+
+impl SpriteAnimation {
+    pub fn new() -> Self {
+        Self {
+            clips: HashMap::new(),
+            current: None,
+            frame: 0,
+            elapsed: 0.0,
+            backwards: false,
+            playing: false,
+            speed: 1.0,
+        }
+    }
+
+    pub fn with_clip(mut self, name: &str, clip: AnimationClip) -> Self {
+        self.add_clip(name, clip);
+        self
+    }
+
+    pub fn add_clip(&mut self, name: &str, clip: AnimationClip) {
+        assert!(!clip.frames.is_empty(), "Animation clip {name} has no frames");
+        self.clips.insert(name.to_string(), clip);
+    }
+
+    // Starts the clip from the first frame, does nothing if it is already playing
+    pub fn play(&mut self, name: &str) {
+        assert!(self.clips.contains_key(name), "No animation clip called {name}");
+
+        if self.playing && self.current.as_deref() == Some(name) {
+            return;
+        }
+
+        self.current = Some(name.to_string());
+        self.frame = 0;
+        self.elapsed = 0.0;
+        self.backwards = false;
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn resume(&mut self) {
+        self.playing = self.current.is_some();
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn get_current_clip(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    pub fn get_frame(&self) -> usize {
+        self.frame
+    }
+
+    // 1 is normal speed, 0 freezes the current frame. Clips only play forwards, a negative speed is
+    // clamped to 0 with a warning.
+    pub fn set_speed(&mut self, speed: f32) {
+        if speed < 0.0 {
+            log::warn!("Sprite animation speed {speed} is negative, using 0");
+        }
+
+        self.speed = speed.max(0.0);
+    }
+
+    pub fn get_speed(&self) -> f32 {
+        self.speed
+    }
+
+    fn get_region(&self) -> Option<Rect> {
+        let clip = self.clips.get(self.current.as_ref()?)?;
+        Some(clip.frames[self.frame].region)
+    }
+
+    // Returns true when a Once clip just finished
+    fn advance(&mut self, delta_time: f32) -> bool {
+        if !self.playing {
+            return false;
+        }
+
+        let clip = &self.clips[self.current.as_ref().unwrap()];
+        let last = clip.frames.len() - 1;
+
+        self.elapsed += delta_time * self.speed;
+
+        loop {
+            // A zero duration would never leave this loop
+            let duration = clip.frames[self.frame].duration.max(0.001);
+
+            if self.elapsed < duration {
+                return false;
+            }
+
+            self.elapsed -= duration;
+
+            match clip.mode {
+                AnimationMode::Loop => self.frame = if self.frame == last { 0 } else { self.frame + 1 },
+                AnimationMode::PingPong if last == 0 => {}
+                AnimationMode::PingPong => {
+                    if self.frame == last {
+                        self.backwards = true;
+                    } else if self.frame == 0 {
+                        self.backwards = false;
+                    }
+
+                    self.frame = if self.backwards { self.frame - 1 } else { self.frame + 1 };
+                }
+                AnimationMode::Once => {
+                    if self.frame == last {
+                        self.elapsed = 0.0;
+                        self.playing = false;
+                        return true;
+                    }
+
+                    self.frame += 1;
+                }
+            }
+        }
+    }
+}
+
+impl Default for SpriteAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct SpriteAnimationPlugin;
+
+impl Plugin for SpriteAnimationPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<TimerPlugin>(),
+            PluginInfo::of::<SpritePlugin>(),
+        ]
+    }
+
+    fn build(app: &mut crate::app::App) {
+        app.storage.world.register_component::<SpriteAnimation>();
+
+        app.schedular
+            .add_system(SystemStage::Update, update_sprite_animations);
+    }
+}
+
+fn update_sprite_animations(storage: &mut Storage) {
+    let delta_time = storage.singletons.get::<Time>().unwrap().delta_time;
+
+    let mut finished = Vec::new();
+
+    for (entity, sprite, animation) in storage
+        .world
+        .query_mut::<(EntityId, Sprite, SpriteAnimation)>()
+    {
+        if animation.advance(delta_time) {
+            finished.push(AnimationFinished {
+                entity: *entity,
+                clip: animation.current.clone().unwrap(),
+            });
+        }
+
+        if let Some(region) = animation.get_region() {
+            sprite.region = region;
+        }
+    }
+
+    for event in finished {
+        storage.emit(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every frame lasts 1 second
+    fn animation(frame_count: usize, mode: AnimationMode) -> SpriteAnimation {
+        let mut clip = AnimationClip::new(mode);
+
+        for _ in 0..frame_count {
+            clip = clip.with_frame(Rect::UNIT, 1.0);
+        }
+
+        let mut animation = SpriteAnimation::new().with_clip("clip", clip);
+        animation.play("clip");
+        animation
+    }
+
+    // Frame after each second
+    fn frames(animation: &mut SpriteAnimation, seconds: usize) -> Vec<usize> {
+        (0..seconds)
+            .map(|_| {
+                animation.advance(1.0);
+                animation.get_frame()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loop_starts_over_after_the_last_frame() {
+        let mut animation = animation(3, AnimationMode::Loop);
+
+        assert_eq!(frames(&mut animation, 5), vec![1, 2, 0, 1, 2]);
+        assert!(animation.is_playing());
+    }
+
+    #[test]
+    fn ping_pong_turns_around_at_both_ends() {
+        let mut animation = animation(3, AnimationMode::PingPong);
+
+        assert_eq!(frames(&mut animation, 6), vec![1, 2, 1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn single_frame_ping_pong_stays_on_it() {
+        let mut animation = animation(1, AnimationMode::PingPong);
+
+        assert_eq!(frames(&mut animation, 3), vec![0, 0, 0]);
+        assert!(animation.is_playing());
+    }
+
+    #[test]
+    fn once_stops_on_the_last_frame() {
+        let mut animation = animation(3, AnimationMode::Once);
+
+        assert!(!animation.advance(1.0));
+        assert!(!animation.advance(1.0));
+        assert_eq!(animation.get_frame(), 2);
+
+        // Finished at the end of the last frame, reported only once
+        assert!(animation.advance(1.0));
+        assert!(!animation.is_playing());
+        assert!(!animation.advance(1.0));
+        assert_eq!(animation.get_frame(), 2);
+    }
+
+    #[test]
+    fn large_steps_skip_frames() {
+        let mut animation = animation(4, AnimationMode::Loop);
+
+        assert!(!animation.advance(2.5));
+        assert_eq!(animation.get_frame(), 2);
+    }
+
+    #[test]
+    fn speed_scales_time_and_never_goes_negative() {
+        let mut animation = animation(3, AnimationMode::Loop);
+
+        animation.set_speed(2.0);
+        animation.advance(1.0);
+        assert_eq!(animation.get_frame(), 2);
+
+        animation.set_speed(-1.0);
+        assert_eq!(animation.get_speed(), 0.0);
+
+        animation.advance(10.0);
+        assert_eq!(animation.get_frame(), 2);
+
+        // Nothing built up while it was stopped
+        animation.set_speed(1.0);
+        animation.advance(0.5);
+        assert_eq!(animation.get_frame(), 2);
+    }
+}
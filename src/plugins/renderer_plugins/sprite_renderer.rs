@@ -1,41 +1,52 @@
+use crate::plugins::core::render_graph::MAIN_PASS;
+use crate::app::PluginInfo;
+use crate::plugins::core::render_plugin::RenderPlugin;
+use crate::plugins::core::camera_plugin::CameraPlugin;
+use crate::plugins::core::asset_storage::AssetStoragePlugin;
+use crate::plugins::core::buffer_vec::BufferVec;
 use crate::{
     ecs::{
         component::Component,
-        singletons::{self, Singletons},
+        singletons::Singletons,
     },
     storage::Storage,
 };
-use bytemuck::{Pod, Zeroable};
-use glam::{Mat3, Vec2, Vec4};
-use hashbrown::HashMap;
-use std::{
-    ops::{Range, RangeBounds},
-    time::{SystemTime, UNIX_EPOCH},
+#[cfg(feature = "text")]
+use glam::Mat3;
+use glam::Vec2;
+use std::ops::Range;
+use wgpu::{
+    util::DeviceExt, BindGroupLayout, Buffer, Device, PipelineLayout, RenderPipeline, ShaderModule,
+    TextureFormat,
 };
-use wgpu::{include_wgsl, util::DeviceExt, BindGroupLayout, Buffer, Device, RenderPipeline};
-use winit::window::Window;
 
 use crate::{
     app::Plugin,
     ecs::world::World,
     math::{
         color::Color,
-        transform2d::{self, AlignedMatrix, Transform2d},
+        rect::Rect,
+        transform2d::{AlignedMatrix, Transform2d},
     },
     plugins::{
-        asset_types::image::Image,
+        asset_types::{
+            image::Image,
+            prefab::{FromPrefab, PrefabFields, PrefabPlugin},
+            shader::Shader,
+            sprite_sheet::SpriteSheet,
+        },
         core::{
-            asset_storage::AssetRef,
-            camera_plugin::{Camera, CameraBindGroup},
+            asset_storage::{AssetRef, AssetStorage},
+            camera_plugin::{CameraBindGroup, Viewport},
             render_plugin::{Gpu, Renderer},
+            shader_reload::{ShaderChanged, ShaderReloadPlugin, ShaderWatcher},
         },
     },
 };
 
-use super::{
-    texture::{self, Texture},
-    vertex::Vertex,
-};
+#[cfg(feature = "text")]
+use super::text::{layout_text, Text2d};
+use super::vertex::Vertex;
 
 const VERTICES: &[Vertex] = &[
     Vertex {
@@ -58,16 +69,27 @@ const VERTICES: &[Vertex] = &[
 
 const INDICES: &[u16] = &[0, 1, 2, 0, 2, 3];
 
+const SHADER_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/plugins/renderer_plugins/sprite_shader.wgsl"
+);
+
+// Used when the file is missing or broken at startup
+const SHADER_SOURCE: &str = include_str!("sprite_shader.wgsl");
+
 pub struct SpriteRendererData {
     pub render_pipeline: RenderPipeline,
+    // Kept around to rebuild the pipeline when the shader changes
+    render_pipeline_layout: PipelineLayout,
+    shader: AssetRef<Shader>,
     pub vertex_buffer: Buffer,
     index_buffer: Buffer,
 
     sprite_data_bind_group: wgpu::BindGroup, // Hmm I really need to think about how to write a render stuff
-    sprite_data_buffer: wgpu::Buffer,
+    sprite_data_bind_group_layout: BindGroupLayout,
 
-    texture_id_transform_list_cache: HashMap<u64, Vec<SpriteInstanceData>>,
-    sprite_data_list: Vec<SpriteInstanceData>,
+    sorted_sprites_cache: Vec<SortedSprite>,
+    sprite_data_list: BufferVec<SpriteInstanceData>,
     texture_id_range: Vec<TextureDrawData>,
 }
 
@@ -76,31 +98,66 @@ struct TextureDrawData {
     texture_id: u64,
 }
 
+struct SortedSprite {
+    z_index: i32,
+    y: f32,
+    texture_id: u64,
+    data: SpriteInstanceData,
+}
+
+// Back to front: lower z_index first, then higher y first so sprites lower on the screen cover the
+// ones behind them, then by texture so equal sprites still end up in one batch.
+// The sort is stable, anything equal keeps query order.
+fn sort_sprites(sprites: &mut [SortedSprite]) {
+    sprites.sort_by(|a, b| {
+        a.z_index
+            .cmp(&b.z_index)
+            .then(b.y.total_cmp(&a.y))
+            .then(a.texture_id.cmp(&b.texture_id))
+    });
+}
+
+// Merges neighbouring sprites with the same texture into one draw call, the ranges index into `sprites`
+fn build_batches(sprites: &[SortedSprite], texture_id_range: &mut Vec<TextureDrawData>) {
+    texture_id_range.clear();
+
+    for (index, sprite) in sprites.iter().enumerate() {
+        match texture_id_range.last_mut() {
+            Some(last) if last.texture_id == sprite.texture_id => last.range.end = index as u32 + 1,
+            _ => texture_id_range.push(TextureDrawData {
+                range: index as u32..index as u32 + 1,
+                texture_id: sprite.texture_id,
+            }),
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Debug)]
 struct SpriteInstanceData {
     color: [f32; 4],
+    uv_rect: [f32; 4],
     matrix: AlignedMatrix, // Todo: instead wasting 8 bytes per instance, I can pack them in array and convert into transform inside shader
     z_index: i32,
     _padding: [u32; 3],
 }
 
 impl SpriteInstanceData {
-    fn new(color: [f32; 4], matrix: AlignedMatrix, z_index: i32) -> Self {
+    fn new(color: [f32; 4], uv_rect: Rect, matrix: AlignedMatrix, z_index: i32) -> Self {
         Self {
             color,
+            uv_rect: [
+                uv_rect.position.x,
+                uv_rect.position.y,
+                uv_rect.size.x,
+                uv_rect.size.y,
+            ],
             matrix,
             z_index,
             _padding: [0; 3],
         }
     }
 
-    const EMPTY: SpriteInstanceData = SpriteInstanceData {
-        color: [0.0; 4],                 // 4
-        matrix: AlignedMatrix::IDENTITY, //12
-        z_index: 1,                      //1
-        _padding: [0; 3],                //3
-    };
 }
 
 pub struct SpritePlugin;
@@ -109,7 +166,7 @@ impl Renderer for SpritePlugin {
     fn render<'pass, 'encoder: 'pass, 'world: 'encoder>(
         &self,
         render_pass: &mut wgpu::RenderPass<'encoder>,
-        world: &'world World,
+        _world: &'world World,
         singletons: &'world Singletons,
     ) {
         let (gpu, camera_data) = singletons.get_many::<(Gpu, CameraBindGroup)>().unwrap();
@@ -120,12 +177,6 @@ impl Renderer for SpritePlugin {
         render_pass.set_vertex_buffer(0, data.vertex_buffer.slice(..));
         render_pass.set_index_buffer(data.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 
-        gpu.queue.write_buffer(
-            &data.sprite_data_buffer,
-            0,
-            bytemuck::cast_slice(&data.sprite_data_list),
-        );
-
         render_pass.set_bind_group(0, &data.sprite_data_bind_group, &[]);
         render_pass.set_bind_group(2, &camera_data.bind_group, &[]);
 
@@ -139,28 +190,34 @@ impl Renderer for SpritePlugin {
 }
 
 impl Plugin for SpritePlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<RenderPlugin>(),
+            PluginInfo::of::<CameraPlugin>(),
+            PluginInfo::of::<AssetStoragePlugin>(),
+            PluginInfo::of::<ShaderReloadPlugin>(),
+            PluginInfo::of::<PrefabPlugin>(),
+        ]
+    }
+
     fn build(app: &mut crate::app::App) {
-        app.storage.world.register_component::<Sprite>();
+        app.storage.register_prefab_component::<Sprite>();
 
-        let (gpu, camera_data) = app
+        let (gpu, camera_data, assets, shader_watcher) = app
             .storage
             .singletons
-            .get_many::<(Gpu, CameraBindGroup)>()
+            .get_many_mut::<(Gpu, CameraBindGroup, AssetStorage, ShaderWatcher)>()
             .unwrap();
 
-        let shader = gpu
-            .device
-            .create_shader_module(include_wgsl!("sprite_shader.wgsl"));
+        let shader = shader_watcher.load(assets, SHADER_PATH, SHADER_SOURCE);
 
-        let sprite_data_buffer = gpu
-            .device
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Sprite data buffer"),
-                contents: bytemuck::cast_slice(
-                    &[SpriteInstanceData::EMPTY; 1_000_000], // Lets assume there wont be more than 512 instance of
-                ),
-                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-            });
+        // Grows when there are more sprites
+        let sprite_data_list = BufferVec::new(
+            &gpu.device,
+            wgpu::BufferUsages::STORAGE,
+            "Sprite data buffer",
+            1024,
+        );
 
         let sprite_data_bind_group_layout =
             gpu.device
@@ -178,14 +235,11 @@ impl Plugin for SpritePlugin {
                     }],
                 });
 
-        let sprite_data_bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Sprite data bind group"),
-            layout: &sprite_data_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: sprite_data_buffer.as_entire_binding(),
-            }],
-        });
+        let sprite_data_bind_group = create_sprite_data_bind_group(
+            &gpu.device,
+            &sprite_data_bind_group_layout,
+            sprite_data_list.buffer(),
+        );
 
         let render_pipeline_layout =
             gpu.device
@@ -199,46 +253,19 @@ impl Plugin for SpritePlugin {
                     push_constant_ranges: &[],
                 });
 
-        let render_pipeline = gpu
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Sprite Render Pipeline"),
-                layout: Some(&render_pipeline_layout),
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: "vs_main",
-                    buffers: &[Vertex::decs()],
-                },
-
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: "fs_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: gpu.surface_config.format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: None,
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
-                },
-
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-
-                multiview: None,
-            });
+        let render_pipeline =
+            assets
+                .get_data(&shader)
+                .build_with_fallback(SHADER_PATH, SHADER_SOURCE, |shader| {
+                    shader.create_pipeline(&gpu.device, SHADER_PATH, |module| {
+                        create_render_pipeline(
+                            &gpu.device,
+                            &render_pipeline_layout,
+                            gpu.surface_config.format,
+                            module,
+                        )
+                    })
+                });
 
         let vertex_buffer = gpu
             .device
@@ -258,81 +285,229 @@ impl Plugin for SpritePlugin {
 
         let sprite_renderer_data = SpriteRendererData {
             render_pipeline,
+            render_pipeline_layout,
+            shader,
             vertex_buffer,
             index_buffer,
-            texture_id_transform_list_cache: HashMap::new(),
+            sorted_sprites_cache: Vec::new(),
             sprite_data_bind_group,
-            sprite_data_buffer,
-            sprite_data_list: Vec::new(),
+            sprite_data_bind_group_layout,
+            sprite_data_list,
             texture_id_range: Vec::new(),
         };
 
-        app.renderers.push(Box::new(SpritePlugin {}));
+        app.render_graph.add_to_pass(MAIN_PASS, Box::new(SpritePlugin {}));
 
         app.storage.singletons.insert(sprite_renderer_data);
 
         app.storage.world.register_component::<Sprite>();
+        app.storage.add_listener::<ShaderChanged>(on_shader_changed);
 
         app.schedular
             .add_system(crate::app::SystemStage::PreRender, update_cache)
     }
 }
 
-pub fn update_cache(world: &mut Storage) {
-    let data = world.singletons.get_mut::<SpriteRendererData>().unwrap();
+fn create_render_pipeline(
+    device: &Device,
+    layout: &PipelineLayout,
+    format: TextureFormat,
+    module: &ShaderModule,
+) -> RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Sprite Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module,
+            entry_point: "vs_main",
+            buffers: &[Vertex::decs()],
+        },
 
-    // Danger used std::mem::take
-    let mut sprite_data_list = std::mem::take(&mut data.sprite_data_list);
-    let mut texture_id_range = std::mem::take(&mut data.texture_id_range);
-    let mut map = std::mem::take(&mut data.texture_id_transform_list_cache);
+        fragment: Some(wgpu::FragmentState {
+            module,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
 
-    for (_, lists) in map.iter_mut() {
-        lists.clear();
+        multiview: None,
+    })
+}
+
+// Keeps the old pipeline if the new source is broken
+fn on_shader_changed(storage: &mut Storage, event: &ShaderChanged) {
+    let (gpu, data, assets) = storage
+        .singletons
+        .get_many_mut::<(Gpu, SpriteRendererData, AssetStorage)>()
+        .unwrap();
+
+    if event.shader.get_id() != data.shader.get_id() {
+        return;
     }
 
-    // Todo: Culling
+    let pipeline = assets
+        .get_data(&data.shader)
+        .create_pipeline(&gpu.device, &event.path, |module| {
+            create_render_pipeline(
+                &gpu.device,
+                &data.render_pipeline_layout,
+                gpu.surface_config.format,
+                module,
+            )
+        });
+
+    if let Some(pipeline) = pipeline {
+        data.render_pipeline = pipeline;
+    }
+}
+
+fn create_sprite_data_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    buffer: &Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Sprite data bind group"),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: buffer.as_entire_binding(),
+        }],
+    })
+}
+
+pub fn update_cache(world: &mut Storage) {
+    // assets is only for text
+    #[cfg_attr(not(feature = "text"), allow(unused_variables))]
+    let (gpu, data, viewport, assets) = world
+        .singletons
+        .get_many_mut::<(Gpu, SpriteRendererData, Viewport, AssetStorage)>()
+        .unwrap();
+
+    let visible_rect = viewport.visible_world_rect();
+
+    data.sorted_sprites_cache.clear();
+
     for (transform2d, sprite) in world.world.query::<(Transform2d, Sprite)>() {
+        if !visible_rect.intersects(&sprite.bounds(transform2d)) {
+            continue;
+        }
+
         // Hmm So sprite has reference to texture
         let texture_id = sprite.image.get_id();
 
-        if let None = map.get_mut(&texture_id) {
-            map.insert(texture_id, Vec::new());
-        }
-
         let mut transform2d = transform2d.clone();
         transform2d.scale *= sprite.size;
 
         let sprite_data = SpriteInstanceData::new(
             sprite.color.into(),
+            sprite.region,
             AlignedMatrix::from_transform(&transform2d),
             sprite.z_index,
         );
 
-        map.get_mut(&texture_id)
-            .expect("No texture id in map")
-            .push(sprite_data);
+        data.sorted_sprites_cache.push(SortedSprite {
+            z_index: sprite.z_index,
+            y: transform2d.position.y,
+            texture_id,
+            data: sprite_data,
+        });
     }
 
-    sprite_data_list.clear();
-    texture_id_range.clear();
-
-    for (texture_id, list) in &mut map {
-        let start_length = sprite_data_list.len();
-        sprite_data_list.append(list);
-        let end_length = sprite_data_list.len();
+    #[cfg(feature = "text")]
+    queue_text(
+        &world.world,
+        assets,
+        gpu,
+        &visible_rect,
+        &mut data.sorted_sprites_cache,
+    );
+
+    sort_sprites(&mut data.sorted_sprites_cache);
+    build_batches(&data.sorted_sprites_cache, &mut data.texture_id_range);
+
+    data.sprite_data_list.clear();
+    for sprite in data.sorted_sprites_cache.iter() {
+        data.sprite_data_list.push(sprite.data);
+    }
 
-        let draw_data = TextureDrawData {
-            range: start_length as u32..end_length as u32,
-            texture_id: *texture_id,
-        };
-        texture_id_range.push(draw_data);
+    if data.sprite_data_list.write_buffer(&gpu.device, &gpu.queue) {
+        data.sprite_data_bind_group = create_sprite_data_bind_group(
+            &gpu.device,
+            &data.sprite_data_bind_group_layout,
+            data.sprite_data_list.buffer(),
+        );
     }
+}
+
+// Every glyph becomes a sprite using the font atlas as texture
+#[cfg(feature = "text")]
+fn queue_text(
+    world: &World,
+    assets: &AssetStorage,
+    gpu: &mut Gpu,
+    visible_rect: &Rect,
+    sprites: &mut Vec<SortedSprite>,
+) {
+    for (transform2d, text) in world.query::<(Transform2d, Text2d)>() {
+        let font_id = text.font.get_id();
+        let font = assets.get_data(&text.font);
+
+        if !gpu.texture_bing_group_map.contains_key(&font_id) {
+            let size = font.atlas.get_size();
+            gpu.create_texture(font_id, "Font atlas", font.atlas.get_data(), size.x, size.y);
+        }
 
-    // God damn borrow checker
-    let data = world.singletons.get_mut::<SpriteRendererData>().unwrap();
-    data.texture_id_transform_list_cache = map;
-    data.sprite_data_list = sprite_data_list;
-    data.texture_id_range = texture_id_range;
+        let text_matrix = transform2d.create_matrix();
+
+        for glyph in layout_text(text, font) {
+            let matrix =
+                text_matrix * Mat3::from_scale_angle_translation(glyph.size, 0.0, glyph.center);
+
+            if !visible_rect.intersects(&Rect::from_center(Vec2::ONE, Vec2::ZERO).transformed(&matrix)) {
+                continue;
+            }
+
+            // Sprite uvs have v going up, atlas regions are top down
+            let region = Rect::new(
+                Vec2::new(glyph.region.size.x, -glyph.region.size.y),
+                Vec2::new(glyph.region.position.x, glyph.region.get_end().y),
+            );
+
+            // Same y for the whole text so its glyphs stay in one batch
+            sprites.push(SortedSprite {
+                z_index: text.z_index,
+                y: transform2d.position.y,
+                texture_id: font_id,
+                data: SpriteInstanceData::new(
+                    text.color.into(),
+                    region,
+                    AlignedMatrix::from_mat3(&matrix),
+                    text.z_index,
+                ),
+            });
+        }
+    }
 }
 
 pub struct Sprite {
@@ -340,6 +515,8 @@ pub struct Sprite {
     pub size: Vec2,
     pub color: Color,
     pub z_index: i32,
+    // Part of the image to draw in uv space, Rect::UNIT is the whole image
+    pub region: Rect,
 }
 
 impl Component for Sprite {}
@@ -351,6 +528,171 @@ impl Sprite {
             color,
             z_index,
             size,
+            region: Rect::UNIT,
+        }
+    }
+
+    pub fn bounds(&self, transform2d: &Transform2d) -> Rect {
+        transform2d.bounds(self.size)
+    }
+
+    pub fn with_region(mut self, region: Rect) -> Self {
+        self.region = region;
+        self
+    }
+}
+
+impl FromPrefab for Sprite {
+    // Either `image`, or `sheet` with a `region` name or `frame` index
+    fn from_prefab(fields: &PrefabFields, assets: &mut AssetStorage) -> Result<Self, String> {
+        let (image, region) = match fields.get_path("sheet") {
+            Some(path) => {
+                let sheet_ref = assets
+                    .get::<SpriteSheet>(&path)
+                    .ok_or(format!("Could not load sprite sheet {path}"))?;
+                let sheet = assets.get_data(&sheet_ref);
+
+                let region = match (fields.get_str("region"), fields.get_i32("frame")) {
+                    (Some(name), _) => sheet.get_region(name),
+                    (None, Some(frame)) => sheet.get_frame(frame as usize),
+                    (None, None) => Some(Rect::UNIT),
+                }
+                .ok_or(format!("Sprite sheet {path} has no such region"))?;
+
+                let image_path = sheet.image.clone();
+                let image = assets
+                    .get::<Image>(&image_path)
+                    .ok_or(format!("Could not load image {image_path}"))?;
+
+                (image, region)
+            }
+            None => {
+                let path = fields.get_path("image").ok_or("Sprite needs an image or a sheet")?;
+                let image = assets
+                    .get::<Image>(&path)
+                    .ok_or(format!("Could not load image {path}"))?;
+
+                (image, Rect::UNIT)
+            }
+        };
+
+        Ok(Self::new(
+            image,
+            fields.get_color("color").unwrap_or(Color::WHITE),
+            fields.get_vec2("size").unwrap_or(Vec2::ONE),
+            fields.get_i32("z_index").unwrap_or(0),
+        )
+        .with_region(region))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted_sprite(z_index: i32, y: f32, texture_id: u64) -> SortedSprite {
+        SortedSprite {
+            z_index,
+            y,
+            texture_id,
+            data: SpriteInstanceData::new(
+                [1.0; 4],
+                Rect::UNIT,
+                AlignedMatrix::IDENTITY,
+                z_index,
+            ),
         }
     }
+
+    fn order(sprites: &[SortedSprite]) -> Vec<(i32, f32, u64)> {
+        sprites
+            .iter()
+            .map(|sprite| (sprite.z_index, sprite.y, sprite.texture_id))
+            .collect()
+    }
+
+    fn ranges(batches: &[TextureDrawData]) -> Vec<(u64, Range<u32>)> {
+        batches
+            .iter()
+            .map(|batch| (batch.texture_id, batch.range.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn sorts_by_z_index_first() {
+        let mut sprites = vec![
+            sorted_sprite(1, 100.0, 1),
+            sorted_sprite(-1, -100.0, 2),
+            sorted_sprite(0, 0.0, 3),
+        ];
+
+        sort_sprites(&mut sprites);
+
+        assert_eq!(
+            order(&sprites),
+            vec![(-1, -100.0, 2), (0, 0.0, 3), (1, 100.0, 1)]
+        );
+    }
+
+    #[test]
+    fn sorts_higher_y_first_within_a_z_index() {
+        let mut sprites = vec![
+            sorted_sprite(0, -10.0, 1),
+            sorted_sprite(0, 20.0, 1),
+            sorted_sprite(0, 5.0, 1),
+        ];
+
+        sort_sprites(&mut sprites);
+
+        assert_eq!(
+            order(&sprites),
+            vec![(0, 20.0, 1), (0, 5.0, 1), (0, -10.0, 1)]
+        );
+    }
+
+    #[test]
+    fn sorts_by_texture_when_z_index_and_y_match() {
+        let mut sprites = vec![
+            sorted_sprite(0, 0.0, 3),
+            sorted_sprite(0, 0.0, 1),
+            sorted_sprite(0, 0.0, 2),
+        ];
+
+        sort_sprites(&mut sprites);
+
+        assert_eq!(order(&sprites), vec![(0, 0.0, 1), (0, 0.0, 2), (0, 0.0, 3)]);
+    }
+
+    #[test]
+    fn merges_neighbouring_sprites_with_the_same_texture() {
+        let sprites = vec![
+            sorted_sprite(0, 0.0, 1),
+            sorted_sprite(0, 0.0, 1),
+            sorted_sprite(0, 0.0, 1),
+        ];
+
+        let mut batches = Vec::new();
+        build_batches(&sprites, &mut batches);
+
+        assert_eq!(ranges(&batches), vec![(1, 0..3)]);
+    }
+
+    #[test]
+    fn splits_batches_when_the_texture_changes() {
+        // Texture 1 shows up twice but not next to each other, the draw order has to be kept
+        let sprites = vec![
+            sorted_sprite(0, 0.0, 1),
+            sorted_sprite(0, 0.0, 1),
+            sorted_sprite(1, 0.0, 2),
+            sorted_sprite(2, 0.0, 1),
+        ];
+
+        let mut batches = vec![TextureDrawData {
+            range: 0..10,
+            texture_id: 7,
+        }];
+        build_batches(&sprites, &mut batches);
+
+        assert_eq!(ranges(&batches), vec![(1, 0..2), (2, 2..3), (1, 3..4)]);
+    }
 }
@@ -13,6 +13,7 @@ struct VertexInput {
 
 struct SpriteData {
     color: vec4<f32>,
+    uv_rect: vec4<f32>, // xy position, zw size
     transform: mat3x3<f32>,
     z_index: i32,
 }
@@ -30,13 +31,16 @@ fn vs_main(
     var out: VertexOutput;
 
     out.color = sprite_data[in.instance_index].color;
-    out.uv = vec2<f32>(in.position.x, in.position.y) + 0.5;
+    let uv_rect = sprite_data[in.instance_index].uv_rect;
+    out.uv = uv_rect.xy + (vec2<f32>(in.position.x, in.position.y) + 0.5) * uv_rect.zw;
 
 
     var pos = sprite_data[in.instance_index].transform * in.position;
     
     out.clip_position = vec4<f32>(projection * pos, 1.0);
-    out.clip_position.z = f32(sprite_data[in.instance_index].z_index) / 1000.0;
+    // No depth buffer, layering comes from the draw order which is sorted by z_index on the cpu.
+    // Negative z would get clipped so keep everything on one plane
+    out.clip_position.z = 0.0;
 
     return out;
 }
@@ -0,0 +1,270 @@
+use glam::Vec2;
+
+use crate::{
+    app::{Plugin, PluginInfo},
+    ecs::component::Component,
+    math::{color::Color, rect::Rect},
+    plugins::{asset_types::font::Font, core::asset_storage::AssetRef},
+};
+
+use super::sprite_renderer::SpritePlugin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+// Drawn by the sprite renderer, so glyphs sort and batch together with sprites.
+// The transform position is the top of the first line, on the left edge, center or right edge
+// depending on the alignment.
+pub struct Text2d {
+    pub text: String,
+    pub font: AssetRef<Font>,
+    pub size: f32, // Line height in world units
+    pub color: Color,
+    pub alignment: TextAlignment,
+    // Lines longer than this break at spaces, in world units
+    pub wrap_width: Option<f32>,
+    pub z_index: i32,
+}
+
+impl Component for Text2d {}
+
+impl Text2d {
+    pub fn new(text: &str, font: AssetRef<Font>, size: f32) -> Self {
+        Self {
+            text: text.to_string(),
+            font,
+            size,
+            color: Color::WHITE,
+            alignment: TextAlignment::Left,
+            wrap_width: None,
+            z_index: 0,
+        }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_alignment(mut self, alignment: TextAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    pub fn with_wrap_width(mut self, wrap_width: f32) -> Self {
+        self.wrap_width = Some(wrap_width);
+        self
+    }
+
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+        self
+    }
+}
+
+// One glyph relative to the text transform
+#[derive(Debug, Clone, Copy)]
+pub struct GlyphQuad {
+    pub center: Vec2,
+    pub size: Vec2,
+    pub region: Rect,
+}
+
+pub fn layout_text(text: &Text2d, font: &Font) -> Vec<GlyphQuad> {
+    let scale = text.size / font.size;
+
+    // Missing characters are drawn as '?', measuring has to use the same glyph or alignment and
+    // wrapping would be off
+    let get_glyph = |character: char| font.get_glyph(character).or_else(|| font.get_glyph('?'));
+
+    let advance = |character: char| {
+        get_glyph(character)
+            .map(|glyph| glyph.advance * scale)
+            .unwrap_or(0.0)
+    };
+
+    let measure = |line: &str| line.chars().map(advance).sum::<f32>();
+
+    let mut lines = Vec::new();
+
+    for paragraph in text.text.split('\n') {
+        let Some(wrap_width) = text.wrap_width else {
+            lines.push(paragraph.to_string());
+            continue;
+        };
+
+        let mut line = String::new();
+
+        for word in paragraph.split(' ') {
+            // A single word wider than wrap_width still gets its own line
+            if !line.is_empty() && measure(&line) + advance(' ') + measure(word) > wrap_width {
+                lines.push(std::mem::take(&mut line));
+            } else if !line.is_empty() {
+                line.push(' ');
+            }
+
+            line.push_str(word);
+        }
+
+        lines.push(line);
+    }
+
+    let mut quads = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let baseline = -(font.ascent + index as f32 * font.line_height) * scale;
+
+        let mut pen = match text.alignment {
+            TextAlignment::Left => 0.0,
+            TextAlignment::Center => -measure(line) * 0.5,
+            TextAlignment::Right => -measure(line),
+        };
+
+        for character in line.chars() {
+            let Some(glyph) = get_glyph(character) else {
+                continue;
+            };
+
+            if glyph.size.x > 0.0 && glyph.size.y > 0.0 {
+                let size = glyph.size * scale;
+                let bottom_left = Vec2::new(pen, baseline) + glyph.offset * scale;
+
+                quads.push(GlyphQuad {
+                    center: bottom_left + size * 0.5,
+                    size,
+                    region: glyph.region,
+                });
+            }
+
+            pen += glyph.advance * scale;
+        }
+    }
+
+    quads
+}
+
+pub struct TextPlugin;
+
+impl Plugin for TextPlugin {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![PluginInfo::of::<SpritePlugin>()]
+    }
+
+    fn build(app: &mut crate::app::App) {
+        app.storage.world.register_component::<Text2d>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::UVec2;
+
+    use crate::plugins::{
+        asset_types::{font::Glyph, image::Image},
+        core::asset_storage::AssetStorage,
+    };
+
+    use super::*;
+
+    // 10 pixel font, every glyph is 6 wide and advances 8, ascent 8 and 12 between lines
+    fn create_font(characters: &str) -> Font {
+        let mut font = Font::new(Image::new(UVec2::ONE, vec![0; 4]), 10.0, 8.0, 12.0);
+
+        for character in characters.chars() {
+            let glyph = Glyph {
+                region: Rect::UNIT,
+                size: Vec2::new(6.0, 8.0),
+                offset: Vec2::ZERO,
+                advance: 8.0,
+            };
+
+            font.insert_glyph(character, glyph);
+        }
+
+        font
+    }
+
+    fn layout(text: &str, font: Font, change: impl Fn(Text2d) -> Text2d) -> Vec<GlyphQuad> {
+        let mut assets = AssetStorage::new();
+        assets.insert(font, "font");
+        let font = assets.get::<Font>("font").unwrap();
+
+        // Size 20 on a 10 pixel font, everything is scaled by 2
+        let text = change(Text2d::new(text, font.clone(), 20.0));
+        layout_text(&text, assets.get_data(&font))
+    }
+
+    fn lefts(quads: &[GlyphQuad]) -> Vec<f32> {
+        quads
+            .iter()
+            .map(|quad| quad.center.x - quad.size.x * 0.5)
+            .collect()
+    }
+
+    fn bottoms(quads: &[GlyphQuad]) -> Vec<f32> {
+        quads
+            .iter()
+            .map(|quad| quad.center.y - quad.size.y * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn glyphs_follow_the_advance() {
+        let quads = layout("ab", create_font("ab"), |text| text);
+
+        assert_eq!(lefts(&quads), vec![0.0, 16.0]);
+        assert_eq!(quads[0].size, Vec2::new(12.0, 16.0));
+        // Ascent below the top
+        assert_eq!(bottoms(&quads), vec![-16.0, -16.0]);
+    }
+
+    #[test]
+    fn missing_characters_measure_like_the_fallback() {
+        // 'x' is drawn and measured as '?', so right alignment ends the line at 0
+        let quads = layout("ax", create_font("a?"), |text| {
+            text.with_alignment(TextAlignment::Right)
+        });
+
+        assert_eq!(lefts(&quads), vec![-32.0, -16.0]);
+    }
+
+    #[test]
+    fn missing_characters_without_a_fallback_take_no_space() {
+        let quads = layout("axa", create_font("a"), |text| text);
+
+        assert_eq!(lefts(&quads), vec![0.0, 16.0]);
+    }
+
+    #[test]
+    fn new_lines_move_down_by_the_line_height() {
+        let quads = layout("a\na\na", create_font("a"), |text| text);
+
+        assert_eq!(lefts(&quads), vec![0.0, 0.0, 0.0]);
+        assert_eq!(bottoms(&quads), vec![-16.0, -40.0, -64.0]);
+    }
+
+    #[test]
+    fn wrapping_breaks_at_spaces() {
+        // "aa aa" is 5 * 16 = 80 wide, only one word fits in 64
+        let quads = layout("aa aa", create_font("a "), |text| {
+            text.with_wrap_width(64.0)
+        });
+
+        assert_eq!(lefts(&quads), vec![0.0, 16.0, 0.0, 16.0]);
+        assert_eq!(bottoms(&quads), vec![-16.0, -16.0, -40.0, -40.0]);
+    }
+
+    #[test]
+    fn words_wider_than_the_wrap_width_keep_their_own_line() {
+        let quads = layout("aaaa a", create_font("a "), |text| {
+            text.with_wrap_width(32.0)
+        });
+
+        assert_eq!(bottoms(&quads), vec![-16.0, -16.0, -16.0, -16.0, -40.0]);
+    }
+}
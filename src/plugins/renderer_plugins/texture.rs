@@ -1,8 +1,6 @@
-use std::{fmt::Debug, num::NonZeroU32};
+use std::fmt::Debug;
 
-use image::GenericImageView;
 
-use crate::plugins::core::render_plugin::Gpu;
 
 pub struct Texture {
     pub texture: wgpu::Texture,
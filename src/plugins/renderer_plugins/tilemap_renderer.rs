@@ -1,42 +1,42 @@
+use crate::plugins::core::render_graph::MAIN_PASS;
+use crate::app::PluginInfo;
+use crate::plugins::core::render_plugin::RenderPlugin;
+use crate::plugins::core::camera_plugin::CameraPlugin;
+use crate::plugins::core::asset_storage::AssetStoragePlugin;
+use crate::plugins::core::buffer_vec::BufferVec;
 use crate::ecs::component::Component;
-use crate::ecs::singletons::{self, Singletons};
+use crate::ecs::singletons::Singletons;
 use crate::storage::Storage;
 use crate::{
     math::{
-        honeycomb::HEXAGON_INDICES,
-        transform2d::{self, AlignedMatrix, Transform2d},
+        rect::Rect,
+        transform2d::{AlignedMatrix, Transform2d},
     },
     plugins::{
-        asset_types::image::Image,
+        asset_types::{image::Image, shader::Shader},
         core::{
-            asset_storage::AssetRef,
-            camera_plugin::{Camera, CameraBindGroup},
+            asset_storage::{AssetRef, AssetStorage},
+            camera_plugin::{CameraBindGroup, Viewport},
             render_plugin::Renderer,
+            shader_reload::{ShaderChanged, ShaderReloadPlugin, ShaderWatcher},
         },
     },
 };
 
-use glam::Vec2;
-use std::{
-    any::{Any, TypeId},
-    borrow::BorrowMut,
-    mem,
-    num::NonZeroU64,
-    ops::Deref,
-    rc::Rc,
-    sync::Arc,
-};
+use glam::{Mat3, Vec2};
+use std::num::NonZeroU64;
 use wgpu::{
-    include_wgsl, util::DeviceExt, BindGroup, BindGroupLayout, BindingResource, BufferBinding,
-    RenderPass, RenderPipeline,
+    util::DeviceExt, BindGroup, BindGroupLayout, BindingResource, BufferBinding, PipelineLayout,
+    RenderPass, RenderPipeline, ShaderModule, TextureFormat,
 };
 
 use crate::{
-    app::Plugin, ecs::world::World, math::color::Color, plugins::core::render_plugin::Gpu,
+    app::Plugin, ecs::world::World, plugins::core::render_plugin::Gpu,
 };
 
-use super::{mesh::Mesh, texture::Texture, vertex::Vertex};
+use super::vertex::Vertex;
 
+// Starting sizes, both grow when a bigger tilemap shows up
 const MAIN_BUFFER_SIZE: u64 = 1 << 20;
 const TILE_DATA_BUFFER_SIZE: u64 = 1 << 14;
 
@@ -61,6 +61,14 @@ const VERTICES: &[Vertex] = &[
 
 const INDICES: &[u16] = &[0, 1, 2, 0, 2, 3];
 
+const SHADER_PATH: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/plugins/renderer_plugins/tilemap_shader.wgsl"
+);
+
+// Used when the file is missing or broken at startup
+const SHADER_SOURCE: &str = include_str!("tilemap_shader.wgsl");
+
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Debug)]
 pub struct TileData {
@@ -100,8 +108,14 @@ impl TileMap {
 
 pub struct TileMapRendererData {
     render_pipeline: RenderPipeline,
+    // Kept around to rebuild the pipeline when the shader changes
+    render_pipeline_layout: PipelineLayout,
+    shader: AssetRef<Shader>,
     tile_map_data_bind_group: BindGroup,
-    tile_map_data_buffer: wgpu::Buffer,
+    tile_map_data_bind_group_layout: BindGroupLayout,
+    tile_map_data_buffer: BufferVec<u8>,
+    // How much of the buffer one tilemap can see from its offset
+    binding_size: u64,
     buffer_offset_list: Vec<u32>,
     data_list: Vec<TileMapData>,
     vertex_buffer: wgpu::Buffer,
@@ -114,7 +128,7 @@ impl Renderer for TileMapRenderer {
     fn render<'pass, 'encoder: 'pass, 'world: 'encoder>(
         &self,
         render_pass: &mut RenderPass<'encoder>,
-        world: &'world World,
+        _world: &'world World,
         singletons: &'world Singletons,
     ) {
         let data = singletons.get::<TileMapRendererData>().unwrap();
@@ -139,22 +153,29 @@ impl Renderer for TileMapRenderer {
             let texture_group = gpu.texture_bing_group_map.get(texture_id).unwrap();
             render_pass.set_bind_group(1, texture_group, &[]);
 
-            render_pass.draw_indexed(0..6, 0, 0..tile_count.clone() as u32);
+            render_pass.draw_indexed(0..6, 0, 0..*tile_count as u32);
         }
     }
 }
 
 impl Plugin for TileMapRenderer {
+    fn dependencies() -> Vec<PluginInfo> {
+        vec![
+            PluginInfo::of::<RenderPlugin>(),
+            PluginInfo::of::<CameraPlugin>(),
+            PluginInfo::of::<AssetStoragePlugin>(),
+            PluginInfo::of::<ShaderReloadPlugin>(),
+        ]
+    }
+
     fn build(app: &mut crate::app::App) {
-        let (gpu, camera_bind_group) = app
+        let (gpu, camera_bind_group, assets, shader_watcher) = app
             .storage
             .singletons
-            .get_many::<(Gpu, CameraBindGroup)>()
+            .get_many_mut::<(Gpu, CameraBindGroup, AssetStorage, ShaderWatcher)>()
             .unwrap();
 
-        let shader = gpu
-            .device
-            .create_shader_module(include_wgsl!("tilemap_shader.wgsl"));
+        let shader = shader_watcher.load(assets, SHADER_PATH, SHADER_SOURCE);
 
         let tile_map_data_bind_group_layout =
             gpu.device
@@ -172,31 +193,19 @@ impl Plugin for TileMapRenderer {
                     }],
                 });
 
-        let align_mask = wgpu::COPY_BUFFER_ALIGNMENT - 1;
-        let padded_size =
-            ((MAIN_BUFFER_SIZE + align_mask) & !align_mask).max(wgpu::COPY_BUFFER_ALIGNMENT);
+        let tile_map_data_buffer = BufferVec::new(
+            &gpu.device,
+            wgpu::BufferUsages::STORAGE,
+            "tile map data buffer",
+            MAIN_BUFFER_SIZE as usize,
+        );
 
-        let tile_map_data_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("tile map data buffer"),
-            size: padded_size,
-            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
-        let buffer_binding = BufferBinding {
-            buffer: &tile_map_data_buffer,
-            offset: 0,
-            size: Some(NonZeroU64::new(TILE_DATA_BUFFER_SIZE).unwrap()),
-        };
-
-        let tile_map_data_bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: None,
-            layout: &tile_map_data_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: BindingResource::Buffer(buffer_binding),
-            }],
-        });
+        let tile_map_data_bind_group = create_tile_map_data_bind_group(
+            &gpu.device,
+            &tile_map_data_bind_group_layout,
+            tile_map_data_buffer.buffer(),
+            TILE_DATA_BUFFER_SIZE,
+        );
 
         let render_pipeline_layout =
             gpu.device
@@ -210,45 +219,19 @@ impl Plugin for TileMapRenderer {
                     push_constant_ranges: &[],
                 });
 
-        let render_pipeline = gpu
-            .device
-            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("Render Pipeline"),
-                layout: Some(&render_pipeline_layout),
-
-                vertex: wgpu::VertexState {
-                    module: &shader,
-                    entry_point: "vs_main",
-                    buffers: &[Vertex::decs()],
-                },
-
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader,
-                    entry_point: "fs_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: gpu.surface_config.format,
-                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-
-                primitive: wgpu::PrimitiveState {
-                    topology: wgpu::PrimitiveTopology::TriangleList,
-                    strip_index_format: None,
-                    front_face: wgpu::FrontFace::Ccw,
-                    cull_mode: Some(wgpu::Face::Back),
-                    unclipped_depth: false,
-                    polygon_mode: wgpu::PolygonMode::Fill,
-                    conservative: false,
-                },
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 1,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                multiview: None,
-            });
+        let render_pipeline =
+            assets
+                .get_data(&shader)
+                .build_with_fallback(SHADER_PATH, SHADER_SOURCE, |shader| {
+                    shader.create_pipeline(&gpu.device, SHADER_PATH, |module| {
+                        create_render_pipeline(
+                            &gpu.device,
+                            &render_pipeline_layout,
+                            gpu.surface_config.format,
+                            module,
+                        )
+                    })
+                });
 
         let vertex_buffer = gpu
             .device
@@ -268,8 +251,12 @@ impl Plugin for TileMapRenderer {
 
         let tile_map_data = TileMapRendererData {
             render_pipeline,
+            render_pipeline_layout,
+            shader,
             tile_map_data_bind_group,
+            tile_map_data_bind_group_layout,
             tile_map_data_buffer,
+            binding_size: TILE_DATA_BUFFER_SIZE,
             buffer_offset_list: Vec::new(),
             data_list: Vec::new(),
 
@@ -278,8 +265,9 @@ impl Plugin for TileMapRenderer {
         };
 
         app.storage.world.register_component::<TileMap>();
+        app.storage.add_listener::<ShaderChanged>(on_shader_changed);
 
-        app.renderers.push(Box::new(TileMapRenderer {}));
+        app.render_graph.add_to_pass(MAIN_PASS, Box::new(TileMapRenderer {}));
 
         app.storage.singletons.insert(tile_map_data);
         app.schedular
@@ -293,25 +281,82 @@ struct TileMapData {
     offset: u32,
 }
 
+fn create_tile_map_data_bind_group(
+    device: &wgpu::Device,
+    layout: &BindGroupLayout,
+    buffer: &wgpu::Buffer,
+    binding_size: u64,
+) -> BindGroup {
+    let buffer_binding = BufferBinding {
+        buffer,
+        offset: 0,
+        size: Some(NonZeroU64::new(binding_size).unwrap()),
+    };
+
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::Buffer(buffer_binding),
+        }],
+    })
+}
+
+// Tiles are centered on their position, the matrix is the one of the tile map
+fn is_tile_visible(
+    tile: &TileData,
+    tile_size: Vec2,
+    tile_map_matrix: &Mat3,
+    visible_rect: &Rect,
+) -> bool {
+    let bounds = Rect::from_center(tile_size, Vec2::from(tile.pos));
+    visible_rect.intersects(&bounds.transformed(tile_map_matrix))
+}
+
 fn prepare_tilemap_data(storage: &mut Storage) {
+    let (gpu, render_data, viewport) = storage
+        .singletons
+        .get_many_mut::<(Gpu, TileMapRendererData, Viewport)>()
+        .unwrap();
+
+    let visible_rect = viewport.visible_world_rect();
+
     let mut offset = 0u32;
 
     let mut offset_list = vec![];
     let mut data_list = vec![];
 
+    let mut binding_size = TILE_DATA_BUFFER_SIZE;
+
+    render_data.tile_map_data_buffer.clear();
+
     // Note: This is coping data of Vec<TileData> into another vec and that might be expensive thing to do
     // for every tilemap every frame.
     for (tile_map, transform2d) in storage.world.query::<(TileMap, Transform2d)>() {
+        let tile_map_matrix = transform2d.create_matrix();
+
+        let visible_tiles: Vec<TileData> = tile_map
+            .tiles
+            .iter()
+            .filter(|tile| {
+                is_tile_visible(tile, tile_map.tile_size, &tile_map_matrix, &visible_rect)
+            })
+            .copied()
+            .collect();
+
+        if visible_tiles.is_empty() {
+            continue;
+        }
+
         offset_list.push(offset);
 
         data_list.push(TileMapData {
             texture_id: tile_map.texture.get_id(),
-            tile_count: tile_map.tiles.len() as u64,
+            tile_count: visible_tiles.len() as u64,
             offset,
         });
 
-        let s = &tile_map.tiles[..];
-
         let matrix = &[AlignedMatrix::from_transform(transform2d)];
 
         let [x, y] = tile_map.tile_size.to_array();
@@ -319,25 +364,158 @@ fn prepare_tilemap_data(storage: &mut Storage) {
 
         let matrix_data: &[u8] = bytemuck::cast_slice(matrix);
         let tile_size_data: &[u8] = bytemuck::cast_slice(&tile_size);
-        let tile_data: &[u8] = bytemuck::cast_slice(&tile_map.tiles);
+        let tile_data: &[u8] = bytemuck::cast_slice(&visible_tiles);
 
-        let data = [matrix_data, tile_size_data, tile_data].concat();
+        let buffer = &mut render_data.tile_map_data_buffer;
+        buffer.extend_from_slice(matrix_data);
+        buffer.extend_from_slice(tile_size_data);
+        buffer.extend_from_slice(tile_data);
 
-        // I should write this buffer into
-        let (gpu, render_data) = storage
-            .singletons
-            .get_many::<(Gpu, TileMapRendererData)>()
-            .unwrap();
+        // Every offset has to be aligned
+        let size = buffer.len() as u32 - offset;
+        let aligned_size = gpu.get_aligned_storage_buffer_size(size);
+        binding_size = binding_size.max(aligned_size as u64);
 
-        // So buffer is written Hurray!
-        gpu.queue
-            .write_buffer(&render_data.tile_map_data_buffer, offset.into(), &data);
+        offset += aligned_size;
+        buffer.resize(offset as usize, 0);
+    }
 
-        let size = data.len() as u32;
-        offset += gpu.get_aligned_storage_buffer_size(size);
+    // The last tilemap still binds binding_size bytes from its offset
+    if let Some(last_offset) = offset_list.last() {
+        let end = *last_offset as u64 + binding_size;
+        let buffer = &mut render_data.tile_map_data_buffer;
+        buffer.resize(buffer.len().max(end as usize), 0);
+    }
+
+    let recreated = render_data
+        .tile_map_data_buffer
+        .write_buffer(&gpu.device, &gpu.queue);
+
+    if recreated || binding_size != render_data.binding_size {
+        render_data.binding_size = binding_size;
+        render_data.tile_map_data_bind_group = create_tile_map_data_bind_group(
+            &gpu.device,
+            &render_data.tile_map_data_bind_group_layout,
+            render_data.tile_map_data_buffer.buffer(),
+            binding_size,
+        );
     }
 
-    let render_data = storage.singletons.get_mut::<TileMapRendererData>().unwrap();
     render_data.buffer_offset_list = offset_list;
     render_data.data_list = data_list;
 }
+
+fn create_render_pipeline(
+    device: &wgpu::Device,
+    layout: &PipelineLayout,
+    format: TextureFormat,
+    module: &ShaderModule,
+) -> RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(layout),
+
+        vertex: wgpu::VertexState {
+            module,
+            entry_point: "vs_main",
+            buffers: &[Vertex::decs()],
+        },
+
+        fragment: Some(wgpu::FragmentState {
+            module,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            unclipped_depth: false,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    })
+}
+
+// Keeps the old pipeline if the new source is broken
+fn on_shader_changed(storage: &mut Storage, event: &ShaderChanged) {
+    let (gpu, data, assets) = storage
+        .singletons
+        .get_many_mut::<(Gpu, TileMapRendererData, AssetStorage)>()
+        .unwrap();
+
+    if event.shader.get_id() != data.shader.get_id() {
+        return;
+    }
+
+    let pipeline = assets
+        .get_data(&data.shader)
+        .create_pipeline(&gpu.device, &event.path, |module| {
+            create_render_pipeline(
+                &gpu.device,
+                &data.render_pipeline_layout,
+                gpu.surface_config.format,
+                module,
+            )
+        });
+
+    if let Some(pipeline) = pipeline {
+        data.render_pipeline = pipeline;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The visible rect of an 800x600 window, -400..400 and -300..300
+    fn visible_rect() -> Rect {
+        let mut viewport = Viewport::default();
+        viewport.set_projection_view(Mat3::from_scale(Vec2::new(2.0 / 800.0, 2.0 / 600.0)));
+        viewport.visible_world_rect()
+    }
+
+    fn is_visible(pos: [f32; 2], tile_map_matrix: Mat3) -> bool {
+        let tile = TileData::new(pos, [1.0; 4]);
+        is_tile_visible(&tile, Vec2::splat(32.0), &tile_map_matrix, &visible_rect())
+    }
+
+    #[test]
+    fn tile_just_inside_the_view_is_kept() {
+        // Left edge at 399
+        assert!(is_visible([415.0, 0.0], Mat3::IDENTITY));
+        // Top edge at -299
+        assert!(is_visible([0.0, -315.0], Mat3::IDENTITY));
+    }
+
+    #[test]
+    fn tile_just_outside_the_view_is_culled() {
+        // Left edge at 401
+        assert!(!is_visible([417.0, 0.0], Mat3::IDENTITY));
+        assert!(!is_visible([0.0, -317.0], Mat3::IDENTITY));
+    }
+
+    #[test]
+    fn tile_map_transform_is_applied_before_culling() {
+        // Inside on its own, moved out of view by the tile map
+        let moved = Mat3::from_translation(Vec2::new(100.0, 0.0));
+        assert!(!is_visible([330.0, 0.0], moved));
+
+        // Outside on its own, scaled back into view by the tile map
+        let scaled = Mat3::from_scale(Vec2::splat(0.5));
+        assert!(is_visible([800.0, 0.0], scaled));
+    }
+}
@@ -0,0 +1,168 @@
+use std::{fmt::Debug, hash::Hash};
+
+use hashbrown::HashMap;
+use log::info;
+
+use crate::{app::SystemStage, storage::Storage};
+
+pub trait StateLabel: Copy + Eq + Hash + Debug + 'static {}
+
+impl<T: Copy + Eq + Hash + Debug + 'static> StateLabel for T {}
+
+// Stages that can have in_state systems. Each one needs its own runner function
+// because Schedular only takes plain fn pointers.
+pub const IN_STATE_STAGES: [SystemStage; 4] = [
+    SystemStage::Input,
+    SystemStage::PreUpdate,
+    SystemStage::Update,
+    SystemStage::PreRender,
+];
+
+pub struct State<S: StateLabel> {
+    current: S,
+    entered: bool,
+}
+
+impl<S: StateLabel> State<S> {
+    pub fn new(initial: S) -> Self {
+        Self {
+            current: initial,
+            entered: false,
+        }
+    }
+
+    pub fn get(&self) -> S {
+        self.current
+    }
+}
+
+// Setting this does not change State right away, the transition happens before the next frame
+pub struct NextState<S: StateLabel> {
+    next: Option<S>,
+}
+
+impl<S: StateLabel> NextState<S> {
+    pub fn new() -> Self {
+        Self { next: None }
+    }
+
+    pub fn set(&mut self, state: S) {
+        self.next = Some(state);
+    }
+}
+
+impl<S: StateLabel> Default for NextState<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+type Systems = Vec<fn(&mut Storage)>;
+
+pub struct StateSystems<S: StateLabel> {
+    on_enter: HashMap<S, Systems>,
+    on_exit: HashMap<S, Systems>,
+    in_state: HashMap<(SystemStage, S), Systems>,
+}
+
+impl<S: StateLabel> StateSystems<S> {
+    pub fn new() -> Self {
+        Self {
+            on_enter: HashMap::new(),
+            on_exit: HashMap::new(),
+            in_state: HashMap::new(),
+        }
+    }
+
+    pub fn add_on_enter(&mut self, state: S, fun: fn(&mut Storage)) {
+        self.on_enter.entry(state).or_default().push(fun);
+    }
+
+    pub fn add_on_exit(&mut self, state: S, fun: fn(&mut Storage)) {
+        self.on_exit.entry(state).or_default().push(fun);
+    }
+
+    pub fn add_in_state(&mut self, stage: SystemStage, state: S, fun: fn(&mut Storage)) {
+        assert!(
+            IN_STATE_STAGES.contains(&stage),
+            "in_state systems can not run in this stage"
+        );
+
+        self.in_state.entry((stage, state)).or_default().push(fun);
+    }
+}
+
+impl<S: StateLabel> Default for StateSystems<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Systems are copied out first so they can borrow storage mutably, same as event listeners
+fn run_systems(storage: &mut Storage, systems: Option<Systems>) {
+    for system in systems.unwrap_or_default() {
+        system(storage);
+    }
+}
+
+pub fn apply_state_transition<S: StateLabel>(storage: &mut Storage) {
+    let state = storage.singletons.get_mut::<State<S>>().unwrap();
+
+    if !state.entered {
+        state.entered = true;
+        let current = state.current;
+
+        let systems = storage.singletons.get::<StateSystems<S>>().unwrap();
+        let on_enter = systems.on_enter.get(&current).cloned();
+        run_systems(storage, on_enter);
+    }
+
+    let next_state = storage.singletons.get_mut::<NextState<S>>().unwrap();
+
+    let Some(next) = next_state.next.take() else {
+        return;
+    };
+
+    let current = storage.singletons.get::<State<S>>().unwrap().current;
+
+    if current == next {
+        return;
+    }
+
+    info!("State {current:?} -> {next:?}");
+
+    let systems = storage.singletons.get::<StateSystems<S>>().unwrap();
+    let on_exit = systems.on_exit.get(&current).cloned();
+    let on_enter = systems.on_enter.get(&next).cloned();
+
+    run_systems(storage, on_exit);
+
+    storage.singletons.get_mut::<State<S>>().unwrap().current = next;
+
+    run_systems(storage, on_enter);
+}
+
+fn run_in_state<S: StateLabel>(storage: &mut Storage, stage: SystemStage) {
+    let current = storage.singletons.get::<State<S>>().unwrap().current;
+
+    let systems = storage.singletons.get::<StateSystems<S>>().unwrap();
+    let in_state = systems.in_state.get(&(stage, current)).cloned();
+
+    run_systems(storage, in_state);
+}
+
+pub fn run_in_state_input<S: StateLabel>(storage: &mut Storage) {
+    run_in_state::<S>(storage, SystemStage::Input);
+}
+
+pub fn run_in_state_pre_update<S: StateLabel>(storage: &mut Storage) {
+    run_in_state::<S>(storage, SystemStage::PreUpdate);
+}
+
+pub fn run_in_state_update<S: StateLabel>(storage: &mut Storage) {
+    run_in_state::<S>(storage, SystemStage::Update);
+}
+
+pub fn run_in_state_pre_render<S: StateLabel>(storage: &mut Storage) {
+    run_in_state::<S>(storage, SystemStage::PreRender);
+}
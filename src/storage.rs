@@ -36,3 +36,9 @@ impl Storage {
         }
     }
 }
+
+impl Default for Storage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,189 @@
+// Renders sprites and tilemaps offscreen and compares them with checked-in pngs.
+// Needs some adapter, the fallback (software) one is enough. Machines without any skip the tests,
+// unless PIXEL_RS_REQUIRE_GPU or CI is set, then a missing adapter fails them.
+// UPDATE_GOLDEN=1 cargo test --test golden writes the current output as the new golden image.
+#![cfg(feature = "render")]
+
+use std::fs;
+
+use glam::{UVec2, Vec2};
+use pixel_rs::{
+    math::{color::Color, transform2d::Transform2d},
+    plugins::{
+        asset_types::image::Image,
+        core::{
+            asset_storage::{Asset, AssetRef, AssetStorage},
+            camera_plugin::CameraPlugin,
+            render_plugin::{Gpu, RenderPlugin},
+            shader_reload::ShaderReloadPlugin,
+        },
+        renderer_plugins::{
+            sprite_renderer::{Sprite, SpritePlugin},
+            tilemap_renderer::{TileData, TileMap, TileMapRenderer},
+        },
+    },
+    prelude::*,
+};
+
+const SIZE: u32 = 64;
+
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+
+// Same lookup as RenderPlugin, which panics when it finds nothing
+fn has_adapter() -> bool {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::util::backend_bits_from_env().unwrap_or(wgpu::Backends::PRIMARY),
+        ..Default::default()
+    });
+
+    [false, true].into_iter().any(|force_fallback_adapter| {
+        let options = wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter,
+            compatible_surface: None,
+        };
+
+        pollster::block_on(instance.request_adapter(&options)).is_some()
+    })
+}
+
+// False means skip, CI has to have an adapter so a skip there would hide that nothing ran
+fn should_run() -> bool {
+    if has_adapter() {
+        return true;
+    }
+
+    let required = ["PIXEL_RS_REQUIRE_GPU", "CI"]
+        .iter()
+        .any(|name| std::env::var_os(name).is_some());
+
+    assert!(
+        !required,
+        "No gpu adapter, but PIXEL_RS_REQUIRE_GPU or CI is set"
+    );
+
+    eprintln!("No gpu adapter, skipping the golden image test");
+    false
+}
+
+fn create_app() -> App {
+    let mut app = App::new();
+    app.register_plugin::<HeadlessPlugins>();
+    app.register_plugin::<RenderPlugin>();
+    app.register_plugin::<ShaderReloadPlugin>();
+    app.register_plugin::<CameraPlugin>();
+    app.register_plugin::<SpritePlugin>();
+    app.register_plugin::<TileMapRenderer>();
+    app.build_plugins();
+
+    // Independent of the size in the user settings
+    app.on_resize(SIZE, SIZE);
+
+    app
+}
+
+// A single colored texture, so sampling can't blur anything
+fn solid_image(
+    assets: &mut AssetStorage,
+    gpu: &mut Gpu,
+    name: &str,
+    color: [u8; 4],
+) -> AssetRef<Image> {
+    let data = color.repeat(4 * 4);
+    assets.insert(Image::new(UVec2::new(4, 4), data.clone()), name);
+
+    let image = assets.get::<Image>(name).unwrap();
+    gpu.create_texture(image.get_id(), name, &data, 4, 4);
+
+    image
+}
+
+fn compare_with_golden(image: &Image, name: &str) {
+    let golden_path = format!("{GOLDEN_DIR}/{name}.png");
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        image.save_png(&golden_path).unwrap();
+        return;
+    }
+
+    let golden = Image::from_binary(fs::read(&golden_path).unwrap());
+    assert_eq!(image.get_size(), golden.get_size());
+
+    // Software and hardware adapters can round a bit differently
+    let max_difference = image
+        .get_data()
+        .iter()
+        .zip(golden.get_data())
+        .map(|(a, b)| a.abs_diff(*b))
+        .max()
+        .unwrap_or(0);
+
+    if max_difference > 2 {
+        let actual_path = format!("{}/{name}.png", env!("CARGO_TARGET_TMPDIR"));
+        image.save_png(&actual_path).unwrap();
+
+        panic!("{name} differs from {golden_path} by up to {max_difference}, output saved to {actual_path}");
+    }
+}
+
+#[test]
+fn sprite_matches_golden_image() {
+    if !should_run() {
+        return;
+    }
+
+    let mut app = create_app();
+
+    let (assets, gpu) = app
+        .storage
+        .singletons
+        .get_many_mut::<(AssetStorage, Gpu)>()
+        .unwrap();
+
+    let image = solid_image(assets, gpu, "golden_red", [255, 0, 0, 255]);
+    let sprite = Sprite::new(
+        image,
+        Color::new(1.0, 1.0, 1.0, 1.0),
+        Vec2::new(16.0, 16.0),
+        0,
+    );
+
+    // 16x16 in the middle of the white 64x64 target, pixel aligned
+    app.storage
+        .world
+        .insert_entity((sprite, Transform2d::IDENTITY));
+
+    app.run_frames(1);
+
+    compare_with_golden(&app.capture_frame(), "sprite");
+}
+
+#[test]
+fn tilemap_matches_golden_image() {
+    if !should_run() {
+        return;
+    }
+
+    let mut app = create_app();
+
+    let (assets, gpu) = app
+        .storage
+        .singletons
+        .get_many_mut::<(AssetStorage, Gpu)>()
+        .unwrap();
+
+    let image = solid_image(assets, gpu, "golden_blue", [0, 0, 255, 255]);
+
+    // Two 16x16 tiles on a diagonal, top left and bottom right of the center, y goes up
+    let mut tile_map = TileMap::new(Vec2::new(16.0, 16.0), image);
+    tile_map.tiles.push(TileData::new([-8.0, 8.0], [1.0; 4]));
+    tile_map.tiles.push(TileData::new([8.0, -8.0], [1.0; 4]));
+
+    app.storage
+        .world
+        .insert_entity((tile_map, Transform2d::IDENTITY));
+
+    app.run_frames(1);
+
+    compare_with_golden(&app.capture_frame(), "tilemap");
+}
@@ -0,0 +1,126 @@
+// Runs the hex city rules headless, no window or gpu. The rules live with the example.
+#[allow(dead_code)]
+#[path = "../examples/hex_city/game/rules/mod.rs"]
+mod rules;
+
+use pixel_rs::{
+    plugins::core::{
+        camera_plugin::{CameraPlugin, Viewport},
+        input::input_plugin::{ButtonState, MouseButton},
+        input::keycode::KeyCode,
+        user_settings::{UserSettings, UserSettingsStore},
+    },
+    prelude::*,
+};
+
+use rules::{
+    level_manager::{LevelManager, RoadAddedEvent},
+    road_placement::RoadCursor,
+    GameState, RulesPlugin,
+};
+
+#[derive(Default)]
+struct AddedRoads(Vec<Hextor>);
+
+fn on_road_added(storage: &mut Storage, event: &RoadAddedEvent) {
+    let added_roads = storage.singletons.get_mut::<AddedRoads>().unwrap();
+    added_roads.0.push(event.new_road);
+}
+
+fn create_app() -> App {
+    let mut app = App::new();
+    app.register_plugin::<HeadlessPlugins>();
+    app.register_plugin::<CameraPlugin>();
+    app.register_plugin::<RulesPlugin>();
+
+    app.storage.singletons.insert(AddedRoads::default());
+    app.storage.add_listener::<RoadAddedEvent>(on_road_added);
+
+    // First frame builds the plugins, starts the level and sets up the viewport
+    app.run_frames(1);
+
+    // HeadlessPlugins never touches the settings file, so the bindings are the defaults on every machine
+    let store = app.storage.singletons.get::<UserSettingsStore>().unwrap();
+    assert_eq!(*store, UserSettingsStore::InMemory);
+
+    app
+}
+
+fn click_tile(app: &mut App, tile: Hextor) {
+    let viewport = app.storage.singletons.get::<Viewport>().unwrap();
+    let position = viewport.world_to_screen(tile.to_vector(32.0).into());
+
+    app.send_cursor_position(position);
+    app.send_mouse_button(MouseButton::Left, ButtonState::Pressed);
+    app.run_frames(1);
+    app.send_mouse_button(MouseButton::Left, ButtonState::Released);
+    app.run_frames(1);
+}
+
+fn is_road(app: &App, tile: Hextor) -> bool {
+    let level_manager = app.storage.singletons.get::<LevelManager>().unwrap();
+    level_manager.is_road(&tile)
+}
+
+#[test]
+fn level_starts_with_two_roads() {
+    let app = create_app();
+
+    assert!(is_road(&app, Hextor::new(0, 0)));
+    assert!(is_road(&app, Hextor::new(1, 0)));
+
+    let added_roads = app.storage.singletons.get::<AddedRoads>().unwrap();
+    assert_eq!(added_roads.0, vec![Hextor::new(0, 0), Hextor::new(1, 0)]);
+}
+
+#[test]
+fn clicking_next_to_a_road_places_one() {
+    let mut app = create_app();
+    let tile = Hextor::new(2, 0);
+
+    click_tile(&mut app, tile);
+
+    let cursor = app.storage.singletons.get::<RoadCursor>().unwrap();
+    assert_eq!(cursor.tile, tile);
+    assert!(is_road(&app, tile));
+
+    let added_roads = app.storage.singletons.get::<AddedRoads>().unwrap();
+    assert_eq!(added_roads.0.last(), Some(&tile));
+}
+
+#[test]
+fn roads_have_to_connect() {
+    let mut app = create_app();
+    let tile = Hextor::new(4, 0);
+
+    click_tile(&mut app, tile);
+
+    assert!(!is_road(&app, tile));
+    assert_eq!(app.storage.singletons.get::<AddedRoads>().unwrap().0.len(), 2);
+}
+
+#[test]
+fn paused_game_ignores_clicks() {
+    let mut app = create_app();
+    let tile = Hextor::new(2, 0);
+
+    let pause = app
+        .storage
+        .singletons
+        .get::<UserSettings>()
+        .unwrap()
+        .key_bindings
+        .pause;
+    assert_eq!(pause, KeyCode::Escape);
+
+    app.send_key(pause, ButtonState::Pressed);
+    app.send_key(pause, ButtonState::Released);
+    app.run_frames(1);
+
+    let state = app.storage.singletons.get::<State<GameState>>().unwrap();
+    assert_eq!(state.get(), GameState::Paused);
+
+    click_tile(&mut app, tile);
+
+    assert!(!is_road(&app, tile));
+}